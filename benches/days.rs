@@ -0,0 +1,77 @@
+//! Benchmarks each day's generator and parts against the checked-in puzzle inputs.
+//!
+//! ```text
+//! cargo bench --bench days [-- dayN]
+//! ```
+
+use aoc_2022::day11::worry::WorryMode;
+use aoc_2022::solutions::Solution;
+use aoc_2022::{
+    day1, day10, day11, day12, day13, day14, day15, day2, day3, day4, day5, day6, day7, day8, day9,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::fs;
+
+/// days with a part that takes long enough to need fewer samples
+const SLOW_DAYS: [u8; 1] = [15];
+
+fn read_input(day: u8) -> String {
+    let path = format!("{}/input/2022/day{}.txt", env!("CARGO_MANIFEST_DIR"), day);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("could not read {}: {}", path, e))
+}
+
+fn bench_day<S: Solution>(c: &mut Criterion) {
+    let input = read_input(S::DAY);
+    let parsed = S::parse(&input).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut group = c.benchmark_group(format!("day{}", S::DAY));
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    if SLOW_DAYS.contains(&S::DAY) {
+        group.sample_size(10);
+    }
+
+    group.bench_function("generator", |b| b.iter(|| S::parse(black_box(&input))));
+    group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&parsed))));
+    group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&parsed))));
+    group.finish();
+}
+
+/// compares day 11's worry representations on part 1, and on part 2 where only residues fit
+fn day11_worry_modes(c: &mut Criterion) {
+    let monkeys = day11::parse_monkey_notes(&read_input(11)).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut group = c.benchmark_group("day11_worry");
+    for mode in [WorryMode::CheckedU64, WorryMode::U128, WorryMode::BigInt] {
+        group.bench_function(format!("part1/{}", mode), |b| {
+            b.iter(|| day11::monkey_business_with(black_box(&monkeys), 20, true, mode))
+        });
+    }
+    group.bench_function("part2/modulo", |b| {
+        b.iter(|| day11::monkey_business_no_relief(black_box(&monkeys)))
+    });
+    group.bench_function("part2/residues", |b| {
+        b.iter(|| day11::monkey_business_residues(black_box(&monkeys)))
+    });
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    bench_day::<day1::Day1>(c);
+    bench_day::<day2::Day2>(c);
+    bench_day::<day3::Day3>(c);
+    bench_day::<day4::Day4>(c);
+    bench_day::<day5::Day5>(c);
+    bench_day::<day6::Day6>(c);
+    bench_day::<day7::Day7>(c);
+    bench_day::<day8::Day8>(c);
+    bench_day::<day9::Day9>(c);
+    bench_day::<day10::Day10>(c);
+    bench_day::<day11::Day11>(c);
+    bench_day::<day12::Day12>(c);
+    bench_day::<day13::Day13>(c);
+    bench_day::<day14::Day14>(c);
+    bench_day::<day15::Day15>(c);
+}
+
+criterion_group!(benches, days, day11_worry_modes);
+criterion_main!(benches);
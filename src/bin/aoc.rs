@@ -0,0 +1,100 @@
+//! Runs a day's solutions without cargo-aoc.
+//!
+//! ```text
+//! aoc --day N [--part P] [--input PATH]
+//! aoc --day 10 --debug --input PATH
+//! ```
+//!
+//! Both parts are run if `--part` is omitted, and the input is read from stdin if `--input` is
+//! omitted (or is `-`). `--debug` runs a day 10 program under the debugger instead, reading
+//! commands from stdin, so the program has to come from a file.
+
+use aoc_2022::cli::{self, RawArgs};
+use aoc_2022::day10::{self, repl};
+use aoc_2022::solutions::{solve, LAST_DAY};
+use std::io;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: aoc --day N [--part P] [--input PATH]
+       aoc --day 10 --debug --input PATH";
+
+#[derive(Debug)]
+struct Args {
+    day: u8,
+    parts: Vec<u8>,
+    input: Option<String>,
+    debug: bool,
+}
+
+fn parse_args(mut args: RawArgs) -> Result<Args, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut debug = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" => day = Some(args.parse_value("--day", "day")?),
+            "--part" => part = Some(args.parse_value("--part", "part")?),
+            "--input" => input = Some(args.value("--input")?),
+            "--debug" => debug = true,
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let day: u8 = day.ok_or("--day is required")?;
+    if !(1..=LAST_DAY).contains(&day) {
+        return Err(format!("day must be between 1 and {}", LAST_DAY));
+    }
+
+    let parts = match part {
+        None => vec![1, 2],
+        Some(part @ (1 | 2)) => vec![part],
+        Some(_) => return Err("part must be 1 or 2".to_owned()),
+    };
+
+    if debug {
+        if day != 10 || part.is_some() {
+            return Err("--debug only runs day 10 programs, without --part".to_owned());
+        }
+        if input.as_deref().is_none_or(|path| path == "-") {
+            return Err("--debug reads commands from stdin, so needs --input PATH".to_owned());
+        }
+    }
+
+    Ok(Args {
+        day,
+        parts,
+        input,
+        debug,
+    })
+}
+
+fn debug(input: &str) -> Result<(), String> {
+    let program = day10::parse_instructions(input).map_err(|e| e.to_string())?;
+    repl::run(&program, io::stdin().lock(), io::stdout().lock()).map_err(|e| e.to_string())
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let input = cli::read_input(args.input.as_deref())?;
+    if args.debug {
+        return debug(&input);
+    }
+
+    for part in args.parts {
+        let solved = solve(args.day, part, &input)
+            .expect("days and parts are validated")
+            .map_err(|e| e.to_string())?;
+        println!("Day {} - Part {} : {}", args.day, part, solved.answer);
+        println!("\tgenerator: {:?},", solved.generator);
+        println!("\trunner: {:?}", solved.runner);
+        println!();
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    cli::run(USAGE, parse_args, run)
+}
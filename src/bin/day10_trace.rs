@@ -0,0 +1,89 @@
+//! Writes a per-cycle trace of a day 10 program as CSV or JSON Lines.
+//!
+//! ```text
+//! day10_trace [--input PATH] [--output PATH] [--format csv|jsonl] [--max-cycles N]
+//! ```
+//!
+//! Pixels are those the puzzle's 40x6 CRT draws; cycles after the screen is full have none.
+//! Programs can loop forever, so at most `--max-cycles` cycles (100000 by default) are traced, and
+//! a program still running after that is reported as an error once its trace is written.
+
+use aoc_2022::cli::{self, RawArgs};
+use aoc_2022::day10::cpu::{CpuSimulator, Program};
+use aoc_2022::day10::crt::Crt;
+use aoc_2022::day10::trace::{TraceFormat, TraceWriter};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+const USAGE: &str =
+    "usage: day10_trace [--input PATH] [--output PATH] [--format csv|jsonl] [--max-cycles N]";
+
+const DEFAULT_MAX_CYCLES: usize = 100_000;
+
+#[derive(Debug)]
+struct Args {
+    input: Option<String>,
+    output: Option<String>,
+    format: TraceFormat,
+    max_cycles: usize,
+}
+
+fn parse_args(mut args: RawArgs) -> Result<Args, String> {
+    let mut input = None;
+    let mut output = None;
+    let mut format = TraceFormat::default();
+    let mut max_cycles = DEFAULT_MAX_CYCLES;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = Some(args.value("--input")?),
+            "--output" => output = Some(args.value("--output")?),
+            "--format" => format = args.value("--format")?.parse()?,
+            "--max-cycles" => max_cycles = args.positive_value("--max-cycles", "cycle count")?,
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(Args {
+        input,
+        output: output.filter(|path| path != "-"),
+        format,
+        max_cycles,
+    })
+}
+
+/// writes the trace of at most `args.max_cycles` cycles, returning whether the program halted
+fn write_trace<W: Write>(program: &Program, args: &Args, writer: W) -> io::Result<bool> {
+    let crt = Crt::default();
+    let mut writer = TraceWriter::new(writer, args.format)?;
+    let mut records = CpuSimulator::new(program).traced(&crt);
+    writer.write_all(records.by_ref().take(args.max_cycles))?;
+    writer.into_inner()?;
+    Ok(records.into_simulator().is_halted())
+}
+
+fn trace(args: Args) -> Result<(), String> {
+    let input = cli::read_input(args.input.as_deref())?;
+    let program = Program::parse(&input).map_err(|e| e.to_string())?;
+
+    let halted = match &args.output {
+        Some(path) => {
+            File::create(path).and_then(|file| write_trace(&program, &args, BufWriter::new(file)))
+        }
+        None => write_trace(&program, &args, io::stdout().lock()),
+    }
+    .map_err(|e| format!("could not write trace: {}", e))?;
+
+    if !halted {
+        return Err(format!(
+            "program still running after {} cycles, so the trace stops there",
+            args.max_cycles
+        ));
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    cli::run(USAGE, parse_args, trace)
+}
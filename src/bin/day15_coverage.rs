@@ -0,0 +1,103 @@
+//! Renders the sensor coverage of a day 15 input as a PPM image.
+//!
+//! ```text
+//! day15_coverage --output PATH [--input PATH] [--window X0,Y0,X1,Y1] [--width PIXELS]
+//!                [--search-space N]
+//! ```
+//!
+//! The distress signal is searched for with `0 <= x, y <= N`, where `N` defaults to the puzzle's
+//! 4000000 (the example uses 20). The window is half-open and defaults to that search space. The
+//! image height follows the window's aspect ratio. Sensors are red, beacons green, and the
+//! distress signal white.
+
+use aoc_2022::cli::{self, RawArgs};
+use aoc_2022::day15::{find_distress_signal, parse_sensor_report, SearchMode, SensorField};
+use aoc_2022::ppm::write_ppm;
+use std::fs::File;
+use std::io::BufWriter;
+use std::ops::Range;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: day15_coverage --output PATH [--input PATH] [--window X0,Y0,X1,Y1] \
+                     [--width PIXELS] [--search-space N]";
+
+const DEFAULT_SEARCH_SPACE: i64 = 4_000_000;
+
+#[derive(Debug)]
+struct Args {
+    input: Option<String>,
+    output: String,
+    x_range: Range<i64>,
+    y_range: Range<i64>,
+    width: usize,
+    search_space: i64,
+}
+
+fn parse_window(window: &str) -> Result<(Range<i64>, Range<i64>), String> {
+    let bounds = window
+        .split(',')
+        .map(|n| n.trim().parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid window '{}'", window))?;
+    match bounds[..] {
+        [x0, y0, x1, y1] if x0 < x1 && y0 < y1 => Ok((x0..x1, y0..y1)),
+        _ => Err(format!("invalid window '{}'", window)),
+    }
+}
+
+fn parse_args(mut args: RawArgs) -> Result<Args, String> {
+    let mut input = None;
+    let mut output = None;
+    let mut window = None;
+    let mut width = 800;
+    let mut search_space = DEFAULT_SEARCH_SPACE;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = Some(args.value("--input")?),
+            "--output" => output = Some(args.value("--output")?),
+            "--window" => window = Some(parse_window(&args.value("--window")?)?),
+            "--width" => width = args.positive_value("--width", "width")?,
+            "--search-space" => {
+                let value = args.value("--search-space")?;
+                search_space = value
+                    .parse()
+                    .ok()
+                    .filter(|&n: &i64| (0..i64::MAX).contains(&n))
+                    .ok_or_else(|| format!("invalid search space '{}'", value))?;
+            }
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let (x_range, y_range) = window.unwrap_or((0..search_space + 1, 0..search_space + 1));
+    Ok(Args {
+        input,
+        output: output.ok_or("--output is required")?,
+        x_range,
+        y_range,
+        width,
+        search_space,
+    })
+}
+
+fn render(args: Args) -> Result<(), String> {
+    let input = cli::read_input(args.input.as_deref())?;
+    let report = parse_sensor_report(&input).map_err(|e| e.to_string())?;
+
+    let x_span = (args.x_range.end - args.x_range.start) as f64;
+    let y_span = (args.y_range.end - args.y_range.start) as f64;
+    let height = ((args.width as f64 * y_span / x_span).round() as usize).max(1);
+
+    let distress = find_distress_signal(&report, args.search_space, SearchMode::Perimeter);
+    let image =
+        SensorField::new(&report).render(args.x_range, args.y_range, args.width, height, distress);
+
+    File::create(&args.output)
+        .and_then(|file| write_ppm(&image, BufWriter::new(file)))
+        .map_err(|e| format!("could not write {}: {}", args.output, e))
+}
+
+fn main() -> ExitCode {
+    cli::run(USAGE, parse_args, render)
+}
@@ -0,0 +1,81 @@
+//! Animates a day 9 rope, one frame per head step.
+//!
+//! ```text
+//! day9_animate [--input PATH] [--knots N] [--width CELLS] [--height CELLS] [--ppm DIR [--scale PIXELS]]
+//! ```
+//!
+//! Frames are printed to stdout as the puzzle's diagrams, or written to `DIR/frame_NNNNNN.ppm` if
+//! `--ppm` is given. The window starts centred on the start and follows the head.
+
+use aoc_2022::cli::{self, RawArgs};
+use aoc_2022::day9::{parse_moves, RopeStepper};
+use aoc_2022::ppm::write_ppm;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: day9_animate [--input PATH] [--knots N] [--width CELLS] \
+[--height CELLS] [--ppm DIR [--scale PIXELS]]";
+
+#[derive(Debug)]
+struct Args {
+    input: Option<String>,
+    knots: usize,
+    width: usize,
+    height: usize,
+    ppm: Option<PathBuf>,
+    scale: usize,
+}
+
+fn parse_args(mut args: RawArgs) -> Result<Args, String> {
+    let mut parsed = Args {
+        input: None,
+        knots: 10,
+        width: 26,
+        height: 21,
+        ppm: None,
+        scale: 8,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => parsed.input = Some(args.value("--input")?),
+            "--knots" => parsed.knots = args.positive_value("--knots", "knots")?,
+            "--width" => parsed.width = args.positive_value("--width", "width")?,
+            "--height" => parsed.height = args.positive_value("--height", "height")?,
+            "--ppm" => parsed.ppm = Some(args.value("--ppm")?.into()),
+            "--scale" => parsed.scale = args.positive_value("--scale", "scale")?,
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn animate(args: Args) -> Result<(), String> {
+    let input = cli::read_input(args.input.as_deref())?;
+    let moves = parse_moves(&input).map_err(|e| e.to_string())?;
+    let mut stepper = RopeStepper::new(args.knots, &moves, args.width, args.height);
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    while let Some(frame) = stepper.next_frame() {
+        match &args.ppm {
+            Some(dir) => {
+                let path = dir.join(format!("frame_{:06}.ppm", frame.step));
+                File::create(&path)
+                    .and_then(|file| write_ppm(&frame.to_image(args.scale), BufWriter::new(file)))
+                    .map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+            }
+            None => writeln!(stdout, "step {}\n{}\n", frame.step, frame.to_ascii())
+                .map_err(|e| e.to_string())?,
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    cli::run(USAGE, parse_args, animate)
+}
@@ -0,0 +1,121 @@
+//! Argument and input handling shared by the binaries in `src/bin`.
+
+use std::io::Read;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::{env, fs, io, vec};
+
+/// Command line arguments, taken a flag and its value at a time.
+#[derive(Debug)]
+pub struct RawArgs {
+    args: vec::IntoIter<String>,
+}
+
+impl RawArgs {
+    pub fn new(args: Vec<String>) -> RawArgs {
+        RawArgs {
+            args: args.into_iter(),
+        }
+    }
+
+    /// the value following `flag`
+    pub fn value(&mut self, flag: &str) -> Result<String, String> {
+        self.args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))
+    }
+
+    /// the value following `flag`, parsed, and described as `what` if it can't be
+    pub fn parse_value<T: FromStr>(&mut self, flag: &str, what: &str) -> Result<T, String> {
+        let value = self.value(flag)?;
+        value
+            .parse()
+            .map_err(|_| format!("invalid {} '{}'", what, value))
+    }
+
+    /// the value following `flag`, which must be a positive integer
+    pub fn positive_value(&mut self, flag: &str, what: &str) -> Result<usize, String> {
+        let value = self.value(flag)?;
+        value
+            .parse()
+            .ok()
+            .filter(|&n: &usize| n > 0)
+            .ok_or_else(|| format!("invalid {} '{}'", what, value))
+    }
+}
+
+impl Iterator for RawArgs {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.args.next()
+    }
+}
+
+/// reads the file at `path`, or stdin if there's no path or it's `-`
+pub fn read_input(path: Option<&str>) -> Result<String, String> {
+    let read = match path {
+        Some(path) if path != "-" => fs::read_to_string(path),
+        _ => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
+        }
+    };
+    read.map_err(|e| format!("could not read input: {}", e))
+}
+
+/// runs a binary: prints `usage` for `--help`, parses the arguments with `parse_args`, then runs
+/// `main` with them. argument errors exit with status 2 and errors from `main` with status 1.
+pub fn run<A, P, M>(usage: &str, parse_args: P, main: M) -> ExitCode
+where
+    P: FnOnce(RawArgs) -> Result<A, String>,
+    M: FnOnce(A) -> Result<(), String>,
+{
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", usage);
+        return ExitCode::SUCCESS;
+    }
+
+    let args = match parse_args(RawArgs::new(args)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n{}", e, usage);
+            return ExitCode::from(2);
+        }
+    };
+
+    match main(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_args(args: &[&str]) -> RawArgs {
+        RawArgs::new(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn test_values() {
+        let mut args = raw_args(&["--day", "12", "--width", "0", "--scale"]);
+        assert_eq!(args.next().as_deref(), Some("--day"));
+        assert_eq!(args.parse_value::<u8>("--day", "day"), Ok(12));
+        assert_eq!(args.next().as_deref(), Some("--width"));
+        assert_eq!(
+            args.positive_value("--width", "width"),
+            Err("invalid width '0'".to_string())
+        );
+        assert_eq!(args.next().as_deref(), Some("--scale"));
+        assert_eq!(
+            args.value("--scale"),
+            Err("missing value for --scale".to_string())
+        );
+    }
+}
@@ -1,18 +1,22 @@
+use crate::error::{parse_number, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 
+const DAY: u8 = 1;
+
 #[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<Vec<u32>> {
+pub fn input_generator(input: &str) -> Result<Vec<Vec<u32>>, AocError> {
     let mut elves = Vec::<Vec<u32>>::new();
     let mut calories = Vec::<u32>::new();
 
-    for line in input.lines() {
+    for (i, line) in input.lines().enumerate() {
         if line.is_empty() {
             elves.push(calories);
             calories = Vec::new();
         } else {
-            calories.push(line.parse().expect("could not parse calories"))
+            calories.push(parse_number(DAY, line, "calories").map_err(|e| e.at_line(i + 1))?)
         }
     }
 
@@ -20,7 +24,7 @@ pub fn input_generator(input: &str) -> Vec<Vec<u32>> {
         elves.push(calories);
     }
 
-    elves
+    Ok(elves)
 }
 
 #[aoc(day1, part1)]
@@ -50,3 +54,26 @@ pub fn calories_held_by_top_3_elves(input: &[Vec<u32>]) -> u32 {
 
     min_heap.iter().map(|r| r.0).sum()
 }
+
+/// Day 1: Calorie Counting
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Vec<u32>>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(most_calories_held(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(calories_held_by_top_3_elves(input))
+    }
+}
@@ -1,138 +1,70 @@
+use crate::error::AocError;
+use crate::ocr::{self, OcrError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
+use cpu::Program;
+use crt::{Crt, CrtError};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Instruction {
-    Noop,
-    AddX(i64),
-}
-
-impl TryFrom<&str> for Instruction {
-    type Error = String;
-
-    fn try_from(instruction: &str) -> Result<Self, Self::Error> {
-        if instruction == "noop" {
-            Ok(Instruction::Noop)
-        } else if let Some(("addx", v)) = instruction.split_once(" ") {
-            let v = v
-                .parse()
-                .map_err(|_| format!("could not parse V: '{}'", v))?;
-            Ok(Instruction::AddX(v))
-        } else {
-            Err(format!("illegal instruction '{}'", instruction))
-        }
-    }
-}
-
-struct CpuSimulator<'a, I>
-where
-    I: Iterator<Item = &'a Instruction>,
-{
-    x: i64,
-    instructions: I,
-    pending_add_x: Option<i64>,
-}
-
-impl<'a, I> CpuSimulator<'a, I>
-where
-    I: Iterator<Item = &'a Instruction>,
-{
-    fn new(iter: I) -> CpuSimulator<'a, I> {
-        CpuSimulator {
-            x: 1,
-            instructions: iter,
-            pending_add_x: None,
-        }
-    }
-}
-
-impl<'a, I> Iterator for CpuSimulator<'a, I>
-where
-    I: Iterator<Item = &'a Instruction>,
-{
-    type Item = i64;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let x = self.x;
-        if let Some(v) = self.pending_add_x {
-            self.x += v;
-            self.pending_add_x = None;
-
-            Some(x)
-        } else if let Some(&instruction) = self.instructions.next() {
-            match instruction {
-                Instruction::AddX(v) => self.pending_add_x = Some(v),
-                Instruction::Noop => {}
-            };
-
-            Some(x)
-        } else {
-            None
-        }
-    }
-}
+pub mod cpu;
+pub mod crt;
+pub mod debugger;
+pub mod repl;
+pub mod trace;
 
-fn interesting<I, T>(mut iter: I) -> [T; 6]
-where
-    I: Iterator<Item = T>,
-{
-    [
-        iter.nth(20 - 1).expect("could not find 20th item"),
-        iter.nth(60 - 20 - 1).expect("could not find 60th item"),
-        iter.nth(100 - 60 - 1).expect("could not find 100th item"),
-        iter.nth(140 - 100 - 1).expect("could not find 140th item"),
-        iter.nth(180 - 140 - 1).expect("could not find 180th item"),
-        iter.nth(220 - 180 - 1).expect("could not find 220th item"),
-    ]
-}
+const DAY: u8 = 10;
 
 #[aoc_generator(day10)]
-pub fn parse_instructions(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(Instruction::try_from)
-        .collect::<Result<_, _>>()
-        .expect("unable to parse instructions")
+pub fn parse_instructions(input: &str) -> Result<Program, AocError> {
+    Program::parse(input)
 }
 
 #[aoc(day10, part1)]
-pub fn sum_interesting_signal_strengths(instructions: &[Instruction]) -> i64 {
-    let signal_strengths = CpuSimulator::new(instructions.iter())
-        .enumerate()
-        .map(|(i, x)| (i + 1) as i64 * x);
+pub fn sum_interesting_signal_strengths(program: &Program) -> Result<i64, CrtError> {
+    Crt::default().signal_strength(program)
+}
 
-    interesting(signal_strengths).iter().sum()
+#[aoc(day10, part2, Render)]
+pub fn render_sprites(program: &Program) -> String {
+    format!("\n{}", crt::to_ascii(&Crt::default().draw(program)))
 }
 
+/// reads the letters the program draws
 #[aoc(day10, part2)]
-pub fn render_sprites(instructions: &[Instruction]) -> String {
-    const DISPLAY_HEIGHT: usize = 6;
-    const DISPLAY_WIDTH: usize = 40;
+pub fn read_sprites(program: &Program) -> Result<String, OcrError> {
+    ocr::read_display(&Crt::default().draw(program))
+}
+
+/// Day 10: Cathode-Ray Tube
+pub struct Day10;
 
-    let mut simulator = CpuSimulator::new(instructions.iter());
+impl Solution for Day10 {
+    const DAY: u8 = DAY;
 
-    let mut display = String::with_capacity((DISPLAY_WIDTH + 1) * DISPLAY_HEIGHT + 1);
-    display.push('\n');
+    type Input = Program;
+    type Answer1 = i64;
+    type Answer2 = String;
 
-    for _ in 0..DISPLAY_HEIGHT {
-        for cursor in 0..DISPLAY_WIDTH {
-            let sprite = simulator.next().expect("not enough instructions") as usize;
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_instructions(input)
+    }
 
-            let char = if cursor >= sprite - 1 && cursor <= sprite + 1 {
-                '#'
-            } else {
-                '.'
-            };
-            display.push(char);
-        }
-        display.push('\n');
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        sum_interesting_signal_strengths(input).map_err(|e| AocError::solve(DAY, e.to_string()))
     }
 
-    display
+    /// fails if any letters can't be read, showing the drawing so they can be read by eye
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        read_sprites(input).map_err(|e| {
+            let display = crt::to_ascii(&Crt::default().draw(input));
+            AocError::solve(DAY, format!("{}, in\n{}", e, display))
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cpu::CpuSimulator;
 
     const INPUT_SMALL: &str = "\
 noop
@@ -289,8 +221,8 @@ noop";
 
     #[test]
     fn test_simulator_small() {
-        let instructions = parse_instructions(INPUT_SMALL);
-        let simulator = CpuSimulator::new(instructions.iter());
+        let instructions = parse_instructions(INPUT_SMALL).unwrap();
+        let simulator = CpuSimulator::new(&instructions);
         let x_states: Vec<_> = simulator.collect();
 
         assert_eq!(x_states, [1, 1, 1, 4, 4]);
@@ -298,13 +230,13 @@ noop";
 
     #[test]
     fn test_simulator_large() {
-        let instructions = parse_instructions(INPUT_LARGE);
+        let instructions = parse_instructions(INPUT_LARGE).unwrap();
 
-        let simulator = CpuSimulator::new(instructions.iter());
-        let x_states = interesting(simulator);
+        let x_states = Crt::default().samples(&instructions);
+        let x_states = x_states.iter().map(|&(_, x)| x).collect::<Vec<_>>();
         assert_eq!(x_states, [21, 19, 18, 21, 16, 18]);
 
-        assert_eq!(sum_interesting_signal_strengths(&instructions), 13140);
+        assert_eq!(sum_interesting_signal_strengths(&instructions), Ok(13140));
     }
 
     #[test]
@@ -318,9 +250,36 @@ noop";
 #######.......#######.......#######.....
 ";
 
-        let instructions = parse_instructions(INPUT_LARGE);
+        let instructions = parse_instructions(INPUT_LARGE).unwrap();
         let render = render_sprites(&instructions);
 
         assert_eq!(EXPECTED_RENDER, render);
     }
+
+    #[test]
+    fn test_unreadable_letters() {
+        let program = parse_instructions(INPUT_LARGE).unwrap();
+        assert_eq!(
+            read_sprites(&program),
+            Err(OcrError::Unrecognised((0..8).collect()))
+        );
+        let error = Day10::part2(&program).unwrap_err().to_string();
+        assert!(
+            error.starts_with(
+                "day 10: unrecognised glyphs at positions 0, 1, 2, 3, 4, 5, 6, 7, in\n##..##.."
+            ),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_render_sprite_off_left_edge() {
+        // x is -1 at the start of the second row, so only the first pixel is lit
+        let source = format!("addx -2\n{}", "noop\n".repeat(238));
+        let program = Program::parse(&source).unwrap();
+        let render = render_sprites(&program);
+        let second_row = render.lines().nth(2).unwrap();
+        assert!(second_row.starts_with("#..."), "{}", second_row);
+    }
 }
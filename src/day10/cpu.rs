@@ -0,0 +1,587 @@
+//! A small CPU for day 10's programs, with a table-driven instruction set.
+//!
+//! Programs have one instruction per line, and lines ending in `:` define labels that jumps can
+//! target. Jump targets are either a label or `@N`, the index of an instruction.
+//!
+//! Registers are 64-bit and arithmetic on them wraps around, as on real hardware, so any program
+//! that parses can run without overflowing.
+//!
+//! ```text
+//! addy 3
+//! loop:
+//! addx 2
+//! addy -1
+//! jnz y loop
+//! ```
+
+use super::crt::Crt;
+use super::trace::Trace;
+use crate::error::{parse_number, AocError};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+const DAY: u8 = 10;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Register {
+    X,
+    Y,
+}
+
+impl Register {
+    pub const ALL: [Register; 2] = [Register::X, Register::Y];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Register::X => "x",
+            Register::Y => "y",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Register> {
+        Register::ALL
+            .into_iter()
+            .find(|register| register.name() == name)
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The CPU's register file. `x` starts at 1 and every other register at 0.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Registers([i64; Register::ALL.len()]);
+
+impl Default for Registers {
+    fn default() -> Self {
+        let mut registers = Registers([0; Register::ALL.len()]);
+        registers[Register::X] = 1;
+        registers
+    }
+}
+
+impl Index<Register> for Registers {
+    type Output = i64;
+
+    fn index(&self, register: Register) -> &Self::Output {
+        &self.0[register as usize]
+    }
+}
+
+impl IndexMut<Register> for Registers {
+    fn index_mut(&mut self, register: Register) -> &mut Self::Output {
+        &mut self.0[register as usize]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Opcode {
+    Noop,
+    AddX,
+    AddY,
+    Mul,
+    Jmp,
+    Jnz,
+}
+
+/// What an instruction takes after its mnemonic.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OperandKind {
+    Register,
+    Value,
+    Target,
+}
+
+/// How an opcode is written, and how many cycles it takes to complete.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OpcodeInfo {
+    pub opcode: Opcode,
+    pub mnemonic: &'static str,
+    pub cycles: u32,
+    pub operands: &'static [OperandKind],
+}
+
+/// every opcode, in the same order as [`Opcode`]
+pub const INSTRUCTION_SET: [OpcodeInfo; 6] = {
+    use OperandKind::*;
+
+    [
+        OpcodeInfo {
+            opcode: Opcode::Noop,
+            mnemonic: "noop",
+            cycles: 1,
+            operands: &[],
+        },
+        OpcodeInfo {
+            opcode: Opcode::AddX,
+            mnemonic: "addx",
+            cycles: 2,
+            operands: &[Value],
+        },
+        OpcodeInfo {
+            opcode: Opcode::AddY,
+            mnemonic: "addy",
+            cycles: 2,
+            operands: &[Value],
+        },
+        OpcodeInfo {
+            opcode: Opcode::Mul,
+            mnemonic: "mul",
+            cycles: 4,
+            operands: &[Register, Value],
+        },
+        OpcodeInfo {
+            opcode: Opcode::Jmp,
+            mnemonic: "jmp",
+            cycles: 1,
+            operands: &[Target],
+        },
+        OpcodeInfo {
+            opcode: Opcode::Jnz,
+            mnemonic: "jnz",
+            cycles: 2,
+            operands: &[Register, Target],
+        },
+    ]
+};
+
+impl Opcode {
+    pub fn info(self) -> &'static OpcodeInfo {
+        &INSTRUCTION_SET[self as usize]
+    }
+}
+
+/// An instruction, with any jump target resolved to an instruction index.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Instruction {
+    Noop,
+    AddX(i64),
+    AddY(i64),
+    /// multiplies a register by a value
+    Mul(Register, i64),
+    Jmp(usize),
+    /// jumps if a register isn't zero
+    Jnz(Register, usize),
+}
+
+impl Instruction {
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Instruction::Noop => Opcode::Noop,
+            Instruction::AddX(_) => Opcode::AddX,
+            Instruction::AddY(_) => Opcode::AddY,
+            Instruction::Mul(..) => Opcode::Mul,
+            Instruction::Jmp(_) => Opcode::Jmp,
+            Instruction::Jnz(..) => Opcode::Jnz,
+        }
+    }
+
+    pub fn cycles(&self) -> u32 {
+        self.opcode().info().cycles
+    }
+
+    /// applies the instruction's effect, wrapping on overflow, and returns the index of the next
+    /// instruction to run
+    fn execute(&self, registers: &mut Registers, index: usize) -> usize {
+        match *self {
+            Instruction::Noop => {}
+            Instruction::AddX(v) => registers[Register::X] = registers[Register::X].wrapping_add(v),
+            Instruction::AddY(v) => registers[Register::Y] = registers[Register::Y].wrapping_add(v),
+            Instruction::Mul(register, v) => {
+                registers[register] = registers[register].wrapping_mul(v)
+            }
+            Instruction::Jmp(target) => return target,
+            Instruction::Jnz(register, target) if registers[register] != 0 => return target,
+            Instruction::Jnz(..) => {}
+        }
+        index + 1
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mnemonic = self.opcode().info().mnemonic;
+        match self {
+            Instruction::Noop => write!(f, "{}", mnemonic),
+            Instruction::AddX(v) | Instruction::AddY(v) => write!(f, "{} {}", mnemonic, v),
+            Instruction::Mul(register, v) => write!(f, "{} {} {}", mnemonic, register, v),
+            Instruction::Jmp(target) => write!(f, "{} @{}", mnemonic, target),
+            Instruction::Jnz(register, target) => {
+                write!(f, "{} {} @{}", mnemonic, register, target)
+            }
+        }
+    }
+}
+
+/// An operand as written, before it's checked against what the opcode expects.
+#[derive(Copy, Clone)]
+enum Operand {
+    Register(Register),
+    Value(i64),
+    Target(usize),
+}
+
+/// A parsed program, with its labels.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    /// each label and the index of the instruction it's before
+    labels: Vec<(String, usize)>,
+}
+
+impl Program {
+    pub fn new(instructions: Vec<Instruction>) -> Program {
+        Program {
+            instructions,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Program, AocError> {
+        // labels can be used before they're defined, so find them all first
+        let mut labels = Vec::new();
+        let mut label_indexes = HashMap::new();
+        let mut index = 0;
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if let Some(label) = line.strip_suffix(':') {
+                let error = |reason: String| AocError::parse(DAY, reason).at_line(i + 1);
+                if label.is_empty() || !label.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(error(format!("invalid label '{}'", label)));
+                }
+                if label_indexes.insert(label, index).is_some() {
+                    return Err(error(format!("duplicate label '{}'", label)));
+                }
+                labels.push((label.to_owned(), index));
+            } else if !line.is_empty() {
+                index += 1;
+            }
+        }
+
+        let mut instructions = Vec::with_capacity(index);
+        for (i, line) in input.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.ends_with(':') {
+                continue;
+            }
+            let instruction =
+                parse_instruction(line, &label_indexes, index).map_err(|e| e.at_line(i + 1))?;
+            instructions.push(instruction);
+        }
+
+        Ok(Program {
+            instructions,
+            labels,
+        })
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Instruction> {
+        self.instructions.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.labels
+            .iter()
+            .map(|(label, index)| (label.as_str(), *index))
+    }
+
+    pub fn label_index(&self, label: &str) -> Option<usize> {
+        self.labels()
+            .find(|(name, _)| *name == label)
+            .map(|(_, index)| index)
+    }
+}
+
+fn parse_instruction(
+    line: &str,
+    labels: &HashMap<&str, usize>,
+    len: usize,
+) -> Result<Instruction, AocError> {
+    let column = |token: &str| token.as_ptr() as usize - line.as_ptr() as usize + 1;
+    let mut tokens = line.split_whitespace();
+    let mnemonic = tokens.next().expect("line is not blank");
+    let info = INSTRUCTION_SET
+        .iter()
+        .find(|info| info.mnemonic == mnemonic)
+        .ok_or_else(|| AocError::parse(DAY, format!("illegal instruction '{}'", line.trim())))?;
+
+    let mut operands = Vec::with_capacity(info.operands.len());
+    for kind in info.operands {
+        let token = tokens.next().ok_or_else(|| {
+            AocError::unexpected_end(DAY, format!("{:?} operand for {}", kind, mnemonic))
+                .at_column(line.len() + 1)
+        })?;
+        let operand = match kind {
+            OperandKind::Register => Register::parse(token)
+                .map(Operand::Register)
+                .ok_or_else(|| AocError::parse(DAY, format!("invalid register '{}'", token))),
+            OperandKind::Value => parse_number(DAY, token, "V").map(Operand::Value),
+            OperandKind::Target => match token.strip_prefix('@') {
+                Some(index) => parse_number(DAY, index, "instruction index")
+                    .and_then(|index: usize| match index {
+                        index if index <= len => Ok(index),
+                        _ => Err(AocError::parse(
+                            DAY,
+                            format!("instruction index {} is out of range", index),
+                        )),
+                    })
+                    .map(Operand::Target),
+                None => labels
+                    .get(token)
+                    .map(|&index| Operand::Target(index))
+                    .ok_or_else(|| AocError::parse(DAY, format!("unknown label '{}'", token))),
+            },
+        };
+        operands.push(operand.map_err(|e| e.at_column(column(token)))?);
+    }
+    if let Some(token) = tokens.next() {
+        return Err(AocError::parse(
+            DAY,
+            format!("unexpected operand '{}' for {}", token, mnemonic),
+        )
+        .at_column(column(token)));
+    }
+
+    Ok(match (info.opcode, &operands[..]) {
+        (Opcode::Noop, []) => Instruction::Noop,
+        (Opcode::AddX, &[Operand::Value(v)]) => Instruction::AddX(v),
+        (Opcode::AddY, &[Operand::Value(v)]) => Instruction::AddY(v),
+        (Opcode::Mul, &[Operand::Register(r), Operand::Value(v)]) => Instruction::Mul(r, v),
+        (Opcode::Jmp, &[Operand::Target(t)]) => Instruction::Jmp(t),
+        (Opcode::Jnz, &[Operand::Register(r), Operand::Target(t)]) => Instruction::Jnz(r, t),
+        _ => unreachable!("operands are parsed from the instruction set"),
+    })
+}
+
+/// An instruction part way through executing.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Pending {
+    pub instruction: Instruction,
+    /// where the instruction is in the program
+    pub index: usize,
+    /// how many more cycles it takes to complete, including the current one
+    pub remaining: u32,
+}
+
+/// What happened during one cycle.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Tick {
+    /// the cycle's number, starting from 1
+    pub cycle: usize,
+    pub index: usize,
+    pub instruction: Instruction,
+    /// the registers during the cycle
+    pub during: Registers,
+    /// the registers once the cycle is over, which differ if the instruction completed
+    pub after: Registers,
+    pub completed: bool,
+}
+
+/// Runs a program a cycle at a time, yielding the value of `x` during each cycle. It stops once
+/// the next instruction would be outside the program.
+#[derive(Debug, Clone)]
+pub struct CpuSimulator<'a> {
+    program: &'a Program,
+    registers: Registers,
+    /// the next instruction to start, once any pending instruction completes
+    next_index: usize,
+    pending: Option<Pending>,
+    cycle: usize,
+}
+
+impl<'a> CpuSimulator<'a> {
+    pub fn new(program: &'a Program) -> CpuSimulator<'a> {
+        CpuSimulator {
+            program,
+            registers: Registers::default(),
+            next_index: 0,
+            pending: None,
+            cycle: 0,
+        }
+    }
+
+    pub fn program(&self) -> &'a Program {
+        self.program
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// how many cycles have completed
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn pending(&self) -> Option<&Pending> {
+        self.pending.as_ref()
+    }
+
+    /// the index of the instruction the next cycle will execute
+    pub fn current_index(&self) -> usize {
+        self.pending
+            .map_or(self.next_index, |pending| pending.index)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pending.is_none() && self.next_index >= self.program.len()
+    }
+
+    /// wraps the simulator to yield a [`TraceRecord`](super::trace::TraceRecord) per cycle, with pixels drawn on `crt`
+    pub fn traced(self, crt: &Crt) -> Trace<'a, '_> {
+        Trace::new(self, crt)
+    }
+
+    /// runs a single cycle, or returns `None` if the program has finished
+    pub fn tick(&mut self) -> Option<Tick> {
+        let mut pending = match self.pending {
+            Some(pending) => pending,
+            None => {
+                let index = self.next_index;
+                let instruction = *self.program.get(index)?;
+                Pending {
+                    instruction,
+                    index,
+                    remaining: instruction.cycles(),
+                }
+            }
+        };
+
+        self.cycle += 1;
+        let during = self.registers;
+        pending.remaining -= 1;
+        let completed = pending.remaining == 0;
+        if completed {
+            self.next_index = pending
+                .instruction
+                .execute(&mut self.registers, pending.index);
+            self.pending = None;
+        } else {
+            self.pending = Some(pending);
+        }
+
+        Some(Tick {
+            cycle: self.cycle,
+            index: pending.index,
+            instruction: pending.instruction,
+            during,
+            after: self.registers,
+            completed,
+        })
+    }
+}
+
+impl Iterator for CpuSimulator<'_> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tick().map(|tick| tick.during[Register::X])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_set_order() {
+        for (i, info) in INSTRUCTION_SET.iter().enumerate() {
+            assert_eq!(info.opcode as usize, i);
+            assert!(info.cycles > 0);
+        }
+    }
+
+    #[test]
+    fn test_labels_and_jumps() {
+        const PROGRAM: &str = "\
+addy 3
+loop:
+addx 2
+addy -1
+jnz y loop
+mul x 10
+jmp end
+addx 1000
+end:";
+        let program = Program::parse(PROGRAM).unwrap();
+        assert_eq!(program.len(), 7);
+        assert_eq!(program.label_index("loop"), Some(1));
+        assert_eq!(program.get(3), Some(&Instruction::Jnz(Register::Y, 1)));
+        assert_eq!(program.get(5), Some(&Instruction::Jmp(7)));
+        assert_eq!(program.get(3).unwrap().to_string(), "jnz y @1");
+
+        let mut simulator = CpuSimulator::new(&program);
+        let cycles = simulator.by_ref().count();
+        assert!(simulator.is_halted());
+        assert_eq!(simulator.registers()[Register::X], 70);
+        assert_eq!(simulator.registers()[Register::Y], 0);
+        // addy, 3 * (addx, addy, jnz), mul, jmp
+        assert_eq!(cycles, 2 + 3 * (2 + 2 + 2) + 4 + 1);
+    }
+
+    #[test]
+    fn test_registers_wrap() {
+        let program = Program::parse(
+            "addx 4611686018427387904
+mul x 4
+addy -1
+mul y -1",
+        )
+        .unwrap();
+        let mut simulator = CpuSimulator::new(&program);
+        simulator.by_ref().for_each(drop);
+        // (1 + 2^62) * 4 = 4 + 2^64
+        assert_eq!(simulator.registers()[Register::X], 4);
+        assert_eq!(simulator.registers()[Register::Y], 1);
+
+        let program = Program::parse(
+            "addx 9223372036854775807
+addx 2",
+        )
+        .unwrap();
+        let mut simulator = CpuSimulator::new(&program);
+        simulator.by_ref().for_each(drop);
+        // x starts at 1
+        assert_eq!(simulator.registers()[Register::X], i64::MIN + 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Program::parse("noop\njmp nowhere").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 10, line 2, column 5: unknown label 'nowhere'"
+        );
+
+        let error = Program::parse("a:\nnoop\na:").unwrap_err();
+        assert_eq!(error.to_string(), "day 10, line 3: duplicate label 'a'");
+
+        let error = Program::parse("mul z 2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 10, line 1, column 5: invalid register 'z'"
+        );
+
+        let error = Program::parse("addx 1 2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 10, line 1, column 8: unexpected operand '2' for addx"
+        );
+    }
+}
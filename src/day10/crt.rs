@@ -0,0 +1,267 @@
+//! The CRT a [`CpuSimulator`] draws on, with configurable geometry.
+
+use super::cpu::{CpuSimulator, Program, Register};
+use crate::grid::{Grid, Position};
+use crate::ppm::Rgb;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+const LIT: Rgb = [120, 255, 120];
+const UNLIT: Rgb = [10, 20, 10];
+
+/// Why a [`Crt`] couldn't be made, or couldn't measure a signal.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CrtError {
+    /// the screen has no pixels
+    EmptyScreen { width: usize, height: usize },
+    /// sampled cycles must be 1-based and strictly ascending
+    UnorderedSamples(Vec<usize>),
+    /// the program halted before reaching this sampled cycle
+    HaltedBeforeSample(usize),
+}
+
+impl Display for CrtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CrtError::EmptyScreen { width, height } => {
+                write!(f, "a {}x{} screen has no pixels", width, height)
+            }
+            CrtError::UnorderedSamples(cycles) => write!(
+                f,
+                "sampled cycles {:?} aren't positive and strictly ascending",
+                cycles
+            ),
+            CrtError::HaltedBeforeSample(cycle) => {
+                write!(f, "program halted before sampled cycle {}", cycle)
+            }
+        }
+    }
+}
+
+impl Error for CrtError {}
+
+/// A display that draws one pixel per cycle, left to right and top to bottom, lighting it if the
+/// sprite (centred on `x`) covers it. Signal strengths are sampled on `sampled_cycles`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Crt {
+    width: usize,
+    height: usize,
+    sprite_width: usize,
+    /// 1-based cycle numbers, in ascending order
+    sampled_cycles: Vec<usize>,
+}
+
+/// the puzzle's 40x6 display and 3 pixel sprite
+impl Default for Crt {
+    fn default() -> Self {
+        Crt {
+            width: 40,
+            height: 6,
+            sprite_width: 3,
+            sampled_cycles: vec![20, 60, 100, 140, 180, 220],
+        }
+    }
+}
+
+impl Crt {
+    /// a `width` by `height` screen, sampling signal strengths on the 1-based `sampled_cycles`,
+    /// which must be in ascending order
+    pub fn new(
+        width: usize,
+        height: usize,
+        sprite_width: usize,
+        sampled_cycles: Vec<usize>,
+    ) -> Result<Crt, CrtError> {
+        if width == 0 || height == 0 {
+            return Err(CrtError::EmptyScreen { width, height });
+        }
+        let ascending = sampled_cycles.first().is_none_or(|&first| first > 0)
+            && sampled_cycles.windows(2).all(|pair| pair[0] < pair[1]);
+        if !ascending {
+            return Err(CrtError::UnorderedSamples(sampled_cycles));
+        }
+        Ok(Crt {
+            width,
+            height,
+            sprite_width,
+            sampled_cycles,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn sprite_width(&self) -> usize {
+        self.sprite_width
+    }
+
+    pub fn sampled_cycles(&self) -> &[usize] {
+        &self.sampled_cycles
+    }
+
+    /// the pixel drawn during (1-based) `cycle`, or `None` if the screen is already full
+    pub fn pixel(&self, cycle: usize) -> Option<Position> {
+        let i = cycle.checked_sub(1)?;
+        let position = (i / self.width, i % self.width);
+        (position.0 < self.height).then_some(position)
+    }
+
+    /// whether a sprite centred on `x` covers column `col`. even width sprites have the extra
+    /// pixel on the right.
+    pub fn sprite_covers(&self, x: i64, col: usize) -> bool {
+        let left = x - (self.sprite_width as i64 - 1) / 2;
+        (left..left + self.sprite_width as i64).contains(&(col as i64))
+    }
+
+    /// runs `program` until the screen is full. pixels after the program halts stay unlit.
+    pub fn draw(&self, program: &Program) -> Grid<bool> {
+        let mut display = Grid::new(self.width, self.height, false);
+        let cycles = CpuSimulator::new(program).zip(1..);
+        for (x, cycle) in cycles {
+            let Some(position) = self.pixel(cycle) else {
+                break;
+            };
+            display[position] = self.sprite_covers(x, position.1);
+        }
+        display
+    }
+
+    /// the value of `x` during each sampled cycle, stopping at the first the program doesn't reach
+    pub fn samples(&self, program: &Program) -> Vec<(usize, i64)> {
+        let mut simulator = CpuSimulator::new(program);
+        let mut sampled = self.sampled_cycles.iter().peekable();
+        let mut samples = Vec::with_capacity(self.sampled_cycles.len());
+        for tick in std::iter::from_fn(|| simulator.tick()) {
+            let Some(&&cycle) = sampled.peek() else {
+                break;
+            };
+            if tick.cycle == cycle {
+                samples.push((cycle, tick.during[Register::X]));
+                sampled.next();
+            }
+        }
+        samples
+    }
+
+    /// the sum of each sampled cycle's number times `x`, wrapping on overflow like the registers
+    /// do, or an error if the program halts before the last sampled cycle
+    pub fn signal_strength(&self, program: &Program) -> Result<i64, CrtError> {
+        let samples = self.samples(program);
+        if let Some(&missed) = self.sampled_cycles.get(samples.len()) {
+            return Err(CrtError::HaltedBeforeSample(missed));
+        }
+        Ok(samples.iter().fold(0i64, |sum, &(cycle, x)| {
+            sum.wrapping_add((cycle as i64).wrapping_mul(x))
+        }))
+    }
+}
+
+/// draws `#` for lit pixels and `.` for unlit ones, a line per row
+pub fn to_ascii(display: &Grid<bool>) -> String {
+    display
+        .rows()
+        .map(|row| {
+            let mut line: String = row.iter().map(|&lit| if lit { '#' } else { '.' }).collect();
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+/// draws two rows per line with half block characters, so pixels come out roughly square
+pub fn to_blocks(display: &Grid<bool>) -> String {
+    let mut blocks = String::new();
+    for top in (0..display.height()).step_by(2) {
+        for col in 0..display.width() {
+            let lower = display.get((top + 1, col)).copied().unwrap_or(false);
+            blocks.push(match (display[(top, col)], lower) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+        blocks.push('\n');
+    }
+    blocks
+}
+
+/// draws each pixel as a `scale` by `scale` square
+pub fn to_image(display: &Grid<bool>, scale: usize) -> Grid<Rgb> {
+    assert!(scale > 0, "scale must be positive");
+    let mut image = Grid::new(display.width() * scale, display.height() * scale, UNLIT);
+    for (row, col) in image.positions() {
+        if display[(row / scale, col / scale)] {
+            image[(row, col)] = LIT;
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprite_covers() {
+        let covered = |sprite_width| {
+            let crt = Crt::new(40, 6, sprite_width, Vec::new()).unwrap();
+            (0..6)
+                .filter(|&col| crt.sprite_covers(2, col))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(covered(3), [1, 2, 3]);
+        assert_eq!(covered(1), [2]);
+        assert_eq!(covered(4), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Crt::new(0, 6, 3, Vec::new()),
+            Err(CrtError::EmptyScreen {
+                width: 0,
+                height: 6
+            })
+        );
+        for cycles in [vec![20, 10], vec![5, 5], vec![0, 1]] {
+            let error = Crt::new(40, 6, 3, cycles.clone()).unwrap_err();
+            assert_eq!(error, CrtError::UnorderedSamples(cycles));
+        }
+
+        let program = Program::parse("noop\naddx 1").unwrap();
+        let crt = Crt::new(3, 1, 1, vec![2, 3, 4]).unwrap();
+        assert_eq!(
+            crt.signal_strength(&program),
+            Err(CrtError::HaltedBeforeSample(4))
+        );
+        assert_eq!(
+            Crt::default()
+                .signal_strength(&program)
+                .unwrap_err()
+                .to_string(),
+            "program halted before sampled cycle 20"
+        );
+    }
+
+    #[test]
+    fn test_small_crt() {
+        let program = Program::parse("noop\naddx 3\naddx -5\nnoop").unwrap();
+        let crt = Crt::new(3, 2, 1, vec![2, 4, 9]).unwrap();
+
+        let display = crt.draw(&program);
+        assert_eq!(to_ascii(&display), ".#.\n...\n");
+        assert_eq!(to_blocks(&display), " ▀ \n");
+        assert_eq!(crt.samples(&program), [(2, 1), (4, 4)]);
+
+        let image = to_image(&display, 2);
+        assert_eq!((image.width(), image.height()), (6, 4));
+        assert_eq!(image[(1, 3)], LIT);
+        assert_eq!(image[(2, 3)], UNLIT);
+    }
+}
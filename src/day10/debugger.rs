@@ -0,0 +1,332 @@
+//! Breakpoints and stepping for [`CpuSimulator`].
+
+use super::cpu::{CpuSimulator, Pending, Program, Register, Registers, Tick};
+use crate::error::{parse_number, AocError};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+const DAY: u8 = 10;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    const ALL: [Comparison; 6] = [
+        Comparison::Less,
+        Comparison::LessOrEqual,
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::GreaterOrEqual,
+        Comparison::Greater,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+
+    pub fn compare(self, a: i64, b: i64) -> bool {
+        match self {
+            Comparison::Less => a < b,
+            Comparison::LessOrEqual => a <= b,
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+            Comparison::GreaterOrEqual => a >= b,
+            Comparison::Greater => a > b,
+        }
+    }
+}
+
+/// Where to stop a program. Each is checked before a cycle runs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Breakpoint {
+    /// before the cycle with this number (starting from 1)
+    Cycle(usize),
+    /// before the instruction at this index starts
+    Instruction(usize),
+    /// whenever a register compares to a value
+    Register(Register, Comparison, i64),
+}
+
+impl Breakpoint {
+    fn hit(&self, simulator: &CpuSimulator) -> bool {
+        match *self {
+            Breakpoint::Cycle(cycle) => simulator.cycle() + 1 == cycle,
+            Breakpoint::Instruction(index) => {
+                simulator.pending().is_none() && simulator.current_index() == index
+            }
+            Breakpoint::Register(register, comparison, value) => {
+                comparison.compare(simulator.registers()[register], value)
+            }
+        }
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {}", cycle),
+            Breakpoint::Instruction(index) => write!(f, "@{}", index),
+            Breakpoint::Register(register, comparison, value) => {
+                write!(f, "{} {} {}", register, comparison.symbol(), value)
+            }
+        }
+    }
+}
+
+/// Parses `cycle N`, `@N` (an instruction index), or a register condition like `x > 20`.
+impl FromStr for Breakpoint {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(cycle) = s.strip_prefix("cycle") {
+            return parse_number(DAY, cycle.trim(), "cycle").map(Breakpoint::Cycle);
+        }
+        if let Some(index) = s.strip_prefix('@') {
+            return parse_number(DAY, index.trim(), "instruction index")
+                .map(Breakpoint::Instruction);
+        }
+
+        let invalid = || AocError::parse(DAY, format!("invalid breakpoint '{}'", s));
+        let tokens: Vec<_> = s.split_whitespace().collect();
+        let [register, comparison, value] = tokens[..] else {
+            return Err(invalid());
+        };
+        let register = Register::parse(register).ok_or_else(invalid)?;
+        let comparison = Comparison::ALL
+            .into_iter()
+            .find(|c| c.symbol() == comparison)
+            .ok_or_else(invalid)?;
+        let value = parse_number(DAY, value, "value")?;
+        Ok(Breakpoint::Register(register, comparison, value))
+    }
+}
+
+/// Why the program stopped running.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Stop {
+    /// the breakpoint with this id was hit
+    Breakpoint(usize),
+    Halted,
+    /// the cycle budget ran out first
+    Limit,
+}
+
+/// Runs a program under breakpoints, a cycle or an instruction at a time.
+#[derive(Debug, Clone)]
+pub struct Debugger<'a> {
+    simulator: CpuSimulator<'a>,
+    /// breakpoints by id, with deleted ones left as `None` so ids stay stable
+    breakpoints: Vec<Option<Breakpoint>>,
+    /// the cycle count when `run` last stopped at a breakpoint
+    stopped_at: Option<usize>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a Program) -> Debugger<'a> {
+        Debugger {
+            simulator: CpuSimulator::new(program),
+            breakpoints: Vec::new(),
+            stopped_at: None,
+        }
+    }
+
+    pub fn simulator(&self) -> &CpuSimulator<'a> {
+        &self.simulator
+    }
+
+    pub fn registers(&self) -> &Registers {
+        self.simulator.registers()
+    }
+
+    /// the instruction part way through executing, such as an `addx` waiting for its second cycle
+    pub fn pending(&self) -> Option<&Pending> {
+        self.simulator.pending()
+    }
+
+    /// adds a breakpoint, returning its id
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.breakpoints.push(Some(breakpoint));
+        self.breakpoints.len() - 1
+    }
+
+    /// removes a breakpoint, returning it if it existed
+    pub fn remove_breakpoint(&mut self, id: usize) -> Option<Breakpoint> {
+        self.breakpoints.get_mut(id)?.take()
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (usize, Breakpoint)> + '_ {
+        self.breakpoints
+            .iter()
+            .enumerate()
+            .filter_map(|(id, breakpoint)| Some((id, (*breakpoint)?)))
+    }
+
+    /// the first breakpoint that would stop the next cycle
+    pub fn breakpoint_hit(&self) -> Option<usize> {
+        self.breakpoints()
+            .find(|(_, breakpoint)| breakpoint.hit(&self.simulator))
+            .map(|(id, _)| id)
+    }
+
+    /// runs one cycle, ignoring breakpoints
+    pub fn step_cycle(&mut self) -> Option<Tick> {
+        self.simulator.tick()
+    }
+
+    /// runs until the current instruction completes, ignoring breakpoints
+    pub fn step_instruction(&mut self) -> Vec<Tick> {
+        let mut ticks = Vec::new();
+        while let Some(tick) = self.simulator.tick() {
+            ticks.push(tick);
+            if tick.completed {
+                break;
+            }
+        }
+        ticks
+    }
+
+    /// runs until a breakpoint is hit, the program halts or `max_cycles` cycles have run, as
+    /// programs that loop may never halt. breakpoints are checked before the first cycle too,
+    /// except where the last run stopped, so that continuing from a breakpoint doesn't stop there
+    /// again straight away.
+    pub fn run(&mut self, max_cycles: usize) -> Stop {
+        for ran in 0.. {
+            let cycle = self.simulator().cycle();
+            if self.stopped_at != Some(cycle) {
+                if let Some(id) = self.breakpoint_hit() {
+                    self.stopped_at = Some(cycle);
+                    return Stop::Breakpoint(id);
+                }
+            }
+            if ran == max_cycles {
+                break;
+            }
+            if self.simulator.tick().is_none() {
+                return Stop::Halted;
+            }
+        }
+        Stop::Limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::cpu::Instruction;
+
+    const PROGRAM: &str = "\
+noop
+addx 3
+addx -5
+noop";
+
+    #[test]
+    fn test_parse_breakpoints() {
+        assert_eq!("cycle 20".parse(), Ok(Breakpoint::Cycle(20)));
+        assert_eq!("@3".parse(), Ok(Breakpoint::Instruction(3)));
+        assert_eq!(
+            "x > 20".parse(),
+            Ok(Breakpoint::Register(Register::X, Comparison::Greater, 20))
+        );
+        assert_eq!(
+            "y <= -1".parse::<Breakpoint>().unwrap().to_string(),
+            "y <= -1"
+        );
+        assert!("x >> 1".parse::<Breakpoint>().is_err());
+        assert!("z == 1".parse::<Breakpoint>().is_err());
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = Program::parse(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(&program);
+        let on_x = debugger.add_breakpoint("x == 4".parse().unwrap());
+        let on_cycle = debugger.add_breakpoint(Breakpoint::Cycle(3));
+        let on_instruction = debugger.add_breakpoint(Breakpoint::Instruction(3));
+
+        assert_eq!(debugger.run(100), Stop::Breakpoint(on_cycle));
+        assert_eq!(debugger.simulator().cycle(), 2);
+        let pending = debugger.pending().unwrap();
+        assert_eq!(pending.instruction, Instruction::AddX(3));
+        assert_eq!(pending.remaining, 1);
+
+        assert_eq!(debugger.run(100), Stop::Breakpoint(on_x));
+        assert_eq!(debugger.simulator().cycle(), 3);
+        assert_eq!(
+            debugger.remove_breakpoint(on_x),
+            Some(Breakpoint::Register(Register::X, Comparison::Equal, 4))
+        );
+
+        assert_eq!(debugger.run(100), Stop::Breakpoint(on_instruction));
+        assert_eq!(debugger.registers()[Register::X], -1);
+        assert_eq!(debugger.run(100), Stop::Halted);
+        assert_eq!(debugger.simulator().cycle(), 6);
+    }
+
+    #[test]
+    fn test_breakpoints_before_first_cycle() {
+        let program = Program::parse(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(&program);
+        let on_cycle = debugger.add_breakpoint("cycle 1".parse().unwrap());
+        assert_eq!(debugger.run(100), Stop::Breakpoint(on_cycle));
+        assert_eq!(debugger.simulator().cycle(), 0);
+        assert_eq!(debugger.run(100), Stop::Halted);
+
+        let mut debugger = Debugger::new(&program);
+        let on_instruction = debugger.add_breakpoint(Breakpoint::Instruction(0));
+        let on_x = debugger.add_breakpoint("x == 1".parse().unwrap());
+        assert_eq!(debugger.run(100), Stop::Breakpoint(on_instruction));
+        assert_eq!(debugger.simulator().cycle(), 0);
+        // x is still 1 during the next cycle, so the condition holds again there
+        assert_eq!(debugger.run(100), Stop::Breakpoint(on_x));
+        assert_eq!(debugger.simulator().cycle(), 1);
+    }
+
+    #[test]
+    fn test_cycle_limit() {
+        let program = Program::parse("loop:\naddx 1\njmp loop").unwrap();
+        let mut debugger = Debugger::new(&program);
+        assert_eq!(debugger.run(1000), Stop::Limit);
+        assert_eq!(debugger.simulator().cycle(), 1000);
+
+        // x grows by one every three cycles, so isn't over 1000 until well after cycle 2000
+        let on_x = debugger.add_breakpoint("x > 1000".parse().unwrap());
+        assert_eq!(debugger.run(1000), Stop::Limit);
+        assert_eq!(debugger.simulator().cycle(), 2000);
+        assert_eq!(debugger.run(10_000), Stop::Breakpoint(on_x));
+        assert_eq!(debugger.registers()[Register::X], 1001);
+    }
+
+    #[test]
+    fn test_stepping() {
+        let program = Program::parse(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(&program);
+
+        assert_eq!(debugger.step_instruction().len(), 1);
+        let tick = debugger.step_cycle().unwrap();
+        assert!(!tick.completed);
+        assert_eq!(debugger.pending().map(|pending| pending.index), Some(1));
+
+        // finishes the addx that's already started
+        let ticks = debugger.step_instruction();
+        assert_eq!(ticks.len(), 1);
+        assert_eq!(ticks[0].after[Register::X], 4);
+        assert_eq!(debugger.pending(), None);
+        assert_eq!(debugger.step_instruction().len(), 2);
+    }
+}
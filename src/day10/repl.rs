@@ -0,0 +1,202 @@
+//! A command line front end for [`Debugger`], run by `aoc --day 10 --debug`.
+
+use super::cpu::{Program, Register, Tick};
+use super::debugger::{Breakpoint, Debugger, Stop};
+use std::io::{self, BufRead, Write};
+
+/// how many cycles `continue` runs for if not told, so a program that never halts can't hang it
+const CONTINUE_CYCLES: usize = 1_000_000;
+
+const HELP: &str = "\
+break SPEC     add a breakpoint: `cycle N`, `@N` (instruction index) or `x > 20`
+delete ID      remove a breakpoint
+breakpoints    list breakpoints
+step           run one cycle
+next           run until the current instruction completes
+continue [N]   run until a breakpoint is hit, the program halts or N cycles (default 1000000) run
+regs           show the registers
+pending        show the instruction part way through executing
+list           show the program, marking the current instruction
+quit           exit";
+
+/// debugs `program`, reading commands from `input` after a `(cpu)` prompt until `quit` or the
+/// end of the input
+pub fn run<R: BufRead, W: Write>(program: &Program, input: R, mut output: W) -> io::Result<()> {
+    let mut debugger = Debugger::new(program);
+    let mut lines = input.lines();
+    loop {
+        write!(output, "(cpu) ")?;
+        output.flush()?;
+        let Some(line) = lines.next() else {
+            return writeln!(output);
+        };
+        if !run_command(&mut debugger, line?.trim(), &mut output)? {
+            return Ok(());
+        }
+    }
+}
+
+fn write_tick<W: Write>(output: &mut W, tick: &Tick) -> io::Result<()> {
+    let registers = Register::ALL
+        .iter()
+        .map(|&r| {
+            if tick.during[r] == tick.after[r] {
+                format!("{}={}", r, tick.during[r])
+            } else {
+                format!("{}={}->{}", r, tick.during[r], tick.after[r])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let state = if tick.completed { "" } else { " (pending)" };
+    writeln!(
+        output,
+        "cycle {}: @{} {}{}  {}",
+        tick.cycle, tick.index, tick.instruction, state, registers
+    )
+}
+
+/// runs a command, returning `false` if the debugger should exit
+fn run_command<W: Write>(debugger: &mut Debugger, line: &str, output: &mut W) -> io::Result<bool> {
+    let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+    match command {
+        "" => {}
+        "help" | "h" => writeln!(output, "{}", HELP)?,
+        "quit" | "q" => return Ok(false),
+        "break" | "b" => match rest.parse::<Breakpoint>() {
+            Ok(breakpoint) => {
+                let id = debugger.add_breakpoint(breakpoint);
+                writeln!(output, "breakpoint {}: {}", id, breakpoint)?;
+            }
+            Err(e) => writeln!(output, "{}", e)?,
+        },
+        "delete" | "d" => match rest.trim().parse() {
+            Ok(id) => match debugger.remove_breakpoint(id) {
+                Some(breakpoint) => writeln!(output, "deleted breakpoint {}: {}", id, breakpoint)?,
+                None => writeln!(output, "no breakpoint {}", id)?,
+            },
+            Err(_) => writeln!(output, "invalid breakpoint id '{}'", rest)?,
+        },
+        "breakpoints" => {
+            for (id, breakpoint) in debugger.breakpoints() {
+                writeln!(output, "{}: {}", id, breakpoint)?;
+            }
+        }
+        "step" | "s" => match debugger.step_cycle() {
+            Some(tick) => write_tick(output, &tick)?,
+            None => writeln!(output, "halted")?,
+        },
+        "next" | "n" => {
+            let ticks = debugger.step_instruction();
+            for tick in &ticks {
+                write_tick(output, tick)?;
+            }
+            if ticks.is_empty() {
+                writeln!(output, "halted")?;
+            }
+        }
+        "continue" | "c" => {
+            let max_cycles = match rest.trim() {
+                "" => CONTINUE_CYCLES,
+                n => match n.parse() {
+                    Ok(n) => n,
+                    Err(_) => {
+                        writeln!(output, "invalid cycle count '{}'", n)?;
+                        return Ok(true);
+                    }
+                },
+            };
+            match debugger.run(max_cycles) {
+                Stop::Breakpoint(id) => writeln!(
+                    output,
+                    "stopped at breakpoint {} before cycle {}",
+                    id,
+                    debugger.simulator().cycle() + 1
+                )?,
+                Stop::Halted => writeln!(
+                    output,
+                    "halted after cycle {}",
+                    debugger.simulator().cycle()
+                )?,
+                Stop::Limit => writeln!(
+                    output,
+                    "still running after {} cycles, stopped before cycle {}",
+                    max_cycles,
+                    debugger.simulator().cycle() + 1
+                )?,
+            }
+        }
+        "regs" | "r" => {
+            for register in Register::ALL {
+                writeln!(output, "{} = {}", register, debugger.registers()[register])?;
+            }
+        }
+        "pending" | "p" => match debugger.pending() {
+            Some(pending) => writeln!(
+                output,
+                "@{} {}, {} more cycle(s)",
+                pending.index, pending.instruction, pending.remaining
+            )?,
+            None => writeln!(output, "nothing pending")?,
+        },
+        "list" | "l" => {
+            let simulator = debugger.simulator();
+            let program = simulator.program();
+            for (index, instruction) in program.instructions().iter().enumerate() {
+                for (label, _) in program.labels().filter(|(_, i)| *i == index) {
+                    writeln!(output, "   {}:", label)?;
+                }
+                let marker = if index == simulator.current_index() {
+                    "=>"
+                } else {
+                    "  "
+                };
+                writeln!(output, "{} @{} {}", marker, index, instruction)?;
+            }
+        }
+        _ => writeln!(output, "unknown command '{}', try `help`", command)?,
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let program = Program::parse("noop\naddx 3\naddx -5\nnoop").unwrap();
+        let commands = "break cycle 3\ncontinue\npending\ndelete 0\ncontinue\nquit\nstep\n";
+        let mut output = Vec::new();
+        run(&program, commands.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let replies = output
+            .split("(cpu) ")
+            .filter(|reply| !reply.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            replies,
+            [
+                "breakpoint 0: cycle 3\n",
+                "stopped at breakpoint 0 before cycle 3\n",
+                "@1 addx 3, 1 more cycle(s)\n",
+                "deleted breakpoint 0: cycle 3\n",
+                "halted after cycle 6\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_continue_stops_looping_program() {
+        let program = Program::parse("loop:\nnoop\njmp loop").unwrap();
+        let mut output = Vec::new();
+        run(&program, "continue\ncontinue 10\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "(cpu) still running after 1000000 cycles, stopped before cycle 1000001\n\
+             (cpu) still running after 10 cycles, stopped before cycle 1000011\n\
+             (cpu) \n"
+        );
+    }
+}
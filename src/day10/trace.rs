@@ -0,0 +1,237 @@
+//! Per-cycle execution traces, written as CSV or JSON Lines so runs can be diffed.
+
+use super::cpu::{CpuSimulator, Instruction, Register, Tick};
+use super::crt::Crt;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// How a trace is written.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum TraceFormat {
+    #[default]
+    Csv,
+    JsonLines,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(TraceFormat::Csv),
+            "jsonl" | "json-lines" => Ok(TraceFormat::JsonLines),
+            _ => Err(format!("unknown trace format '{}'", s)),
+        }
+    }
+}
+
+impl Display for TraceFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceFormat::Csv => write!(f, "csv"),
+            TraceFormat::JsonLines => write!(f, "jsonl"),
+        }
+    }
+}
+
+/// The pixel the CRT drew during a cycle.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Pixel {
+    pub row: usize,
+    pub col: usize,
+    pub lit: bool,
+}
+
+/// One line of a trace.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TraceRecord {
+    pub cycle: usize,
+    /// the index of the instruction being executed
+    pub index: usize,
+    pub instruction: Instruction,
+    /// `x` during the cycle
+    pub x_before: i64,
+    /// `x` once the cycle is over
+    pub x_after: i64,
+    /// `None` once the screen is full
+    pub pixel: Option<Pixel>,
+}
+
+impl TraceRecord {
+    pub fn new(tick: &Tick, crt: &Crt) -> TraceRecord {
+        let x_before = tick.during[Register::X];
+        let pixel = crt.pixel(tick.cycle).map(|(row, col)| Pixel {
+            row,
+            col,
+            lit: crt.sprite_covers(x_before, col),
+        });
+        TraceRecord {
+            cycle: tick.cycle,
+            index: tick.index,
+            instruction: tick.instruction,
+            x_before,
+            x_after: tick.after[Register::X],
+            pixel,
+        }
+    }
+}
+
+/// Wraps a simulator, yielding a [`TraceRecord`] for each cycle.
+#[derive(Debug, Clone)]
+pub struct Trace<'a, 'c> {
+    simulator: CpuSimulator<'a>,
+    crt: &'c Crt,
+}
+
+impl<'a, 'c> Trace<'a, 'c> {
+    pub fn new(simulator: CpuSimulator<'a>, crt: &'c Crt) -> Trace<'a, 'c> {
+        Trace { simulator, crt }
+    }
+
+    pub fn into_simulator(self) -> CpuSimulator<'a> {
+        self.simulator
+    }
+}
+
+impl Iterator for Trace<'_, '_> {
+    type Item = TraceRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.simulator
+            .tick()
+            .map(|tick| TraceRecord::new(&tick, self.crt))
+    }
+}
+
+/// Writes trace records in one format, starting with a header for CSV.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    format: TraceFormat,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// starts a trace, writing the header straight away so that even an empty trace has one
+    pub fn new(mut writer: W, format: TraceFormat) -> io::Result<TraceWriter<W>> {
+        if format == TraceFormat::Csv {
+            writeln!(
+                writer,
+                "cycle,index,instruction,x_before,x_after,row,col,lit"
+            )?;
+        }
+        Ok(TraceWriter { writer, format })
+    }
+
+    pub fn write(&mut self, record: &TraceRecord) -> io::Result<()> {
+        match self.format {
+            TraceFormat::Csv => {
+                let pixel = record.pixel.map_or_else(
+                    || ",,".to_string(),
+                    |pixel| format!("{},{},{}", pixel.row, pixel.col, pixel.lit),
+                );
+                // instructions never contain commas or quotes, so nothing needs quoting
+                writeln!(
+                    self.writer,
+                    "{},{},{},{},{},{}",
+                    record.cycle,
+                    record.index,
+                    record.instruction,
+                    record.x_before,
+                    record.x_after,
+                    pixel
+                )?;
+            }
+            TraceFormat::JsonLines => {
+                let pixel = record.pixel.map_or_else(
+                    || "null".to_string(),
+                    |pixel| {
+                        format!(
+                            r#"{{"row":{},"col":{},"lit":{}}}"#,
+                            pixel.row, pixel.col, pixel.lit
+                        )
+                    },
+                );
+                writeln!(
+                    self.writer,
+                    r#"{{"cycle":{},"index":{},"instruction":"{}","x_before":{},"x_after":{},"pixel":{}}}"#,
+                    record.cycle,
+                    record.index,
+                    record.instruction,
+                    record.x_before,
+                    record.x_after,
+                    pixel
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// writes every record, returning how many there were
+    pub fn write_all<I: IntoIterator<Item = TraceRecord>>(
+        &mut self,
+        records: I,
+    ) -> io::Result<usize> {
+        let mut count = 0;
+        for record in records {
+            self.write(&record)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::cpu::Program;
+
+    fn trace(format: TraceFormat) -> String {
+        let program = Program::parse("noop\naddx 3").unwrap();
+        let crt = Crt::new(2, 1, 3, Vec::new()).unwrap();
+        let mut writer = TraceWriter::new(Vec::new(), format).unwrap();
+        let records = CpuSimulator::new(&program).traced(&crt);
+        assert_eq!(writer.write_all(records).unwrap(), 3);
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            trace(TraceFormat::Csv),
+            "\
+cycle,index,instruction,x_before,x_after,row,col,lit
+1,0,noop,1,1,0,0,true
+2,1,addx 3,1,1,0,1,true
+3,1,addx 3,1,4,,,
+"
+        );
+    }
+
+    #[test]
+    fn test_empty_csv_has_header() {
+        let writer = TraceWriter::new(Vec::new(), TraceFormat::Csv).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "cycle,index,instruction,x_before,x_after,row,col,lit\n"
+        );
+        let writer = TraceWriter::new(Vec::new(), TraceFormat::JsonLines).unwrap();
+        assert!(writer.into_inner().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let trace = trace(TraceFormat::JsonLines);
+        let lines = trace.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            r#"{"cycle":1,"index":0,"instruction":"noop","x_before":1,"x_after":1,"pixel":{"row":0,"col":0,"lit":true}}"#
+        );
+        assert!(lines[2].ends_with(r#""x_before":1,"x_after":4,"pixel":null}"#));
+    }
+}
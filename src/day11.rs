@@ -1,9 +1,28 @@
+use crate::error::{parse_number, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
+use expr::Expr;
+use keep_away::{DivideBy, KeepAway, NoRelief, Relief};
+use num_bigint::BigUint;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::iter::Enumerate;
+use std::str::Lines;
+use validate::{Diagnostic, Severity};
+use worry::{Modular, Residues, Worry, WorryError, WorryMode};
+
+pub mod cycles;
+pub mod expr;
+pub mod keep_away;
+pub mod validate;
+pub mod worry;
+
+const DAY: u8 = 11;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Monkey {
+pub struct Monkey {
     items: Vec<Item>,
-    operation: Operation,
+    operation: Expr,
     test: Test,
 }
 
@@ -12,31 +31,6 @@ struct Item {
     worry: usize,
 }
 
-impl Item {
-    fn apply(&mut self, op: &Operation) {
-        self.worry = match op {
-            Operation::Add(x) => self.worry + x,
-            Operation::Multiply(x) => self.worry * x,
-            Operation::Square => self.worry * self.worry,
-        }
-    }
-
-    fn relieve(&mut self) {
-        self.worry /= 3;
-    }
-
-    fn relieve_modulo(&mut self, modulo: usize) {
-        self.worry %= modulo;
-    }
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Operation {
-    Add(usize),
-    Multiply(usize),
-    Square,
-}
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Test {
     divisible_by: usize,
@@ -45,8 +39,8 @@ struct Test {
 }
 
 impl Test {
-    fn target(&self, item: &Item) -> usize {
-        if item.worry % self.divisible_by == 0 {
+    fn target_if(&self, divisible: bool) -> usize {
+        if divisible {
             self.target_true
         } else {
             self.target_false
@@ -54,56 +48,130 @@ impl Test {
     }
 }
 
+/// returns the next line (and its line number), stripped of `prefix`
+fn next_line_with_prefix<'a>(
+    lines: &mut Enumerate<Lines<'a>>,
+    expected: &str,
+    prefix: &str,
+) -> Result<(usize, &'a str), AocError> {
+    let (i, line) = lines
+        .next()
+        .ok_or_else(|| AocError::unexpected_end(DAY, expected))?;
+    let line = line.strip_prefix(prefix).ok_or_else(|| {
+        AocError::parse(DAY, format!("invalid {} format: '{}'", expected, line)).at_line(i + 1)
+    })?;
+    Ok((i + 1, line))
+}
+
+/// Why the notes couldn't be used.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NotesError {
+    Parse(AocError),
+    /// every diagnostic [`validate::validate`] found, at least one of them an error
+    Invalid(Vec<Diagnostic>),
+}
+
+impl Display for NotesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NotesError::Parse(e) => write!(f, "{}", e),
+            NotesError::Invalid(diagnostics) => {
+                let errors = diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+                    .map(|diagnostic| diagnostic.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "invalid monkeys: {}", errors.join("; "))
+            }
+        }
+    }
+}
+
+impl Error for NotesError {}
+
+impl From<NotesError> for AocError {
+    fn from(e: NotesError) -> AocError {
+        match e {
+            NotesError::Parse(e) => e,
+            invalid => AocError::parse(DAY, invalid.to_string()),
+        }
+    }
+}
+
+/// parses the notes, along with any warnings from [`validate::validate`], failing if it finds any
+/// errors
+pub fn parse_monkey_notes_with_warnings(
+    input: &str,
+) -> Result<(Vec<Monkey>, Vec<Diagnostic>), NotesError> {
+    let monkeys = parse_monkey_notes_unchecked(input).map_err(NotesError::Parse)?;
+
+    let diagnostics = validate::validate(&monkeys);
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity() == Severity::Error)
+    {
+        return Err(NotesError::Invalid(diagnostics));
+    }
+
+    Ok((monkeys, diagnostics))
+}
+
+/// parses the notes, ignoring warnings, and fails if [`validate::validate`] finds any errors
 #[aoc_generator(day11)]
-fn parse_monkey_notes(input: &str) -> Vec<Monkey> {
+pub fn parse_monkey_notes(input: &str) -> Result<Vec<Monkey>, AocError> {
+    let (monkeys, _) = parse_monkey_notes_with_warnings(input)?;
+    Ok(monkeys)
+}
+
+fn parse_monkey_notes_unchecked(input: &str) -> Result<Vec<Monkey>, AocError> {
     let mut monkeys = Vec::new();
 
-    let mut lines = input.lines();
+    let mut lines = input.lines().enumerate();
 
     loop {
-        let monkey_declaration = lines.next().expect("no monkey declaration line");
-        assert_eq!(monkey_declaration, format!("Monkey {}:", monkeys.len()));
+        let declaration = format!("Monkey {}:", monkeys.len());
+        let (line, rest) = next_line_with_prefix(&mut lines, "monkey declaration", &declaration)?;
+        if !rest.is_empty() {
+            return Err(AocError::parse(
+                DAY,
+                format!("expected '{}', found trailing '{}'", declaration, rest),
+            )
+            .at_line(line));
+        }
 
-        let items = lines.next().expect("no starting items line");
+        let (line, items) =
+            next_line_with_prefix(&mut lines, "starting items", "  Starting items:")?;
         let items = items
-            .strip_prefix("  Starting items: ")
-            .expect("invalid starting items line format");
-        let items = items
-            .split(", ")
-            .map(|worry| Item {
-                worry: worry.parse().expect("could not parse starting item worry"),
+            .split(',')
+            .map(str::trim)
+            .filter(|worry| !worry.is_empty())
+            .map(|worry| {
+                Ok(Item {
+                    worry: parse_number(DAY, worry, "starting item worry")?,
+                })
             })
-            .collect();
-
-        let operation = lines.next().expect("no operation line");
-        let operation = operation
-            .strip_prefix("  Operation: new = old ")
-            .expect("invalid operation line format");
-        let (operation, operand) = operation.split_once(' ').expect("invalid operation format");
-        let operation = match operation {
-            "+" => Operation::Add(operand.parse().expect("could not parse operand")),
-            "*" if operand == "old" => Operation::Square,
-            "*" => Operation::Multiply(operand.parse().expect("could not parse operand")),
-            _ => panic!("unexpected operation: '{}'", operation),
-        };
+            .collect::<Result<_, AocError>>()
+            .map_err(|e| e.at_line(line))?;
 
-        let test = lines.next().expect("no test line");
-        let divisible_by = test
-            .strip_prefix("  Test: divisible by ")
-            .expect("invalid test line");
-        let divisible_by = divisible_by.parse().expect("invalid test number format");
+        const OPERATION_PREFIX: &str = "  Operation: new = ";
+        let (line, operation) = next_line_with_prefix(&mut lines, "operation", OPERATION_PREFIX)?;
+        let operation = Expr::parse_from_column(operation, OPERATION_PREFIX.len() + 1)
+            .map_err(|e| e.at_line(line))?;
 
-        let test_true = lines.next().expect("no test_true line");
-        let target_true = test_true
-            .strip_prefix("    If true: throw to monkey ")
-            .expect("invalid test_true line");
-        let target_true = target_true.parse().expect("invalid test_true target");
+        let (line, divisible_by) =
+            next_line_with_prefix(&mut lines, "test", "  Test: divisible by ")?;
+        let divisible_by =
+            parse_number(DAY, divisible_by, "test number").map_err(|e| e.at_line(line))?;
 
-        let test_false = lines.next().expect("no test_false line");
-        let target_false = test_false
-            .strip_prefix("    If false: throw to monkey ")
-            .expect("invalid test_false line");
-        let target_false = target_false.parse().expect("invalid test_false target");
+        let (line, target_true) =
+            next_line_with_prefix(&mut lines, "test_true", "    If true: throw to monkey ")?;
+        let target_true =
+            parse_number(DAY, target_true, "test_true target").map_err(|e| e.at_line(line))?;
+
+        let (line, target_false) =
+            next_line_with_prefix(&mut lines, "test_false", "    If false: throw to monkey ")?;
+        let target_false =
+            parse_number(DAY, target_false, "test_false target").map_err(|e| e.at_line(line))?;
 
         let monkey = Monkey {
             items,
@@ -118,90 +186,114 @@ fn parse_monkey_notes(input: &str) -> Vec<Monkey> {
         monkeys.push(monkey);
 
         match lines.next() {
-            Some("") => {} // empty line separates next monkey
+            Some((_, "")) => {} // empty line separates next monkey
             None => break,
-            Some(line) => panic!("expected empty line (monkey separator), not '{}'", line),
+            Some((i, line)) => {
+                return Err(AocError::parse(
+                    DAY,
+                    format!("expected empty line (monkey separator), not '{}'", line),
+                )
+                .at_line(i + 1))
+            }
         }
     }
 
-    monkeys
+    Ok(monkeys)
 }
 
 #[aoc(day11, part1)]
-fn monkey_business(input: &[Monkey]) -> usize {
-    let mut monkeys: Vec<_> = input.iter().cloned().collect();
-
-    let mut num_inspections = vec![0; monkeys.len()];
-
-    const ROUNDS: usize = 20;
-
-    for _round in 0..ROUNDS {
-        for i in 0..monkeys.len() {
-            assert_ne!(i, monkeys[i].test.target_true);
-            assert_ne!(i, monkeys[i].test.target_false);
-
-            num_inspections[i] += monkeys[i].items.len();
+pub fn monkey_business(input: &[Monkey]) -> Result<usize, WorryError> {
+    play::<u64, _>(input, 20, DivideBy(3))
+}
 
-            for j in 0..monkeys[i].items.len() {
-                let mut item = monkeys[i].items[j];
-                item.apply(&monkeys[i].operation);
-                item.relieve();
+/// keeps worry levels modulo the product of the divisors, which only `+` and `*` respect, so
+/// operations using `-` or `/` are rejected
+#[aoc(day11, part2)]
+pub fn monkey_business_no_relief(input: &[Monkey]) -> Result<usize, WorryError> {
+    play::<Modular, _>(input, 10_000, NoRelief)
+}
 
-                let target = monkeys[i].test.target(&item);
-                monkeys[target].items.push(item);
-            }
+/// the monkey business after `rounds` rounds, with worry levels held as `W` and relieved by
+/// `relief`
+fn play<W: Worry, R: Relief<W>>(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: R,
+) -> Result<usize, WorryError> {
+    let mut game = KeepAway::<W, R>::new(monkeys, relief)?;
+    game.play(rounds)?;
+    Ok(game.monkey_business(2))
+}
 
-            monkeys[i].items.clear();
-        }
+/// as [`play`], dividing worry levels by 3 if `relief` is set
+fn play_relieved_if<W: Worry>(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: bool,
+) -> Result<usize, WorryError> {
+    if relief {
+        play::<W, _>(monkeys, rounds, DivideBy(3))
+    } else {
+        play::<W, _>(monkeys, rounds, NoRelief)
     }
-
-    num_inspections.sort_unstable();
-    num_inspections[num_inspections.len() - 2..]
-        .iter()
-        .product()
 }
 
-#[aoc(day11, part2)]
-fn monkey_business_no_relief(input: &[Monkey]) -> usize {
-    let mut monkeys: Vec<_> = input.iter().cloned().collect();
-
-    let modulo = monkeys.iter().map(|m| m.test.divisible_by).product();
+/// the monkey business after `rounds` rounds, with worry levels held in the representation
+/// `mode`. relief divides worry levels by 3 after each inspection, as in part 1.
+pub fn monkey_business_with(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: bool,
+    mode: WorryMode,
+) -> Result<usize, WorryError> {
+    match mode {
+        WorryMode::CheckedU64 => play_relieved_if::<u64>(monkeys, rounds, relief),
+        WorryMode::U128 => play_relieved_if::<u128>(monkeys, rounds, relief),
+        WorryMode::BigInt => play_relieved_if::<BigUint>(monkeys, rounds, relief),
+        WorryMode::Residues => play_relieved_if::<Residues>(monkeys, rounds, relief),
+        WorryMode::Modular => play_relieved_if::<Modular>(monkeys, rounds, relief),
+    }
+}
 
-    let mut num_inspections = vec![0; monkeys.len()];
+#[aoc(day11, part2, Residues)]
+pub fn monkey_business_residues(input: &[Monkey]) -> Result<usize, WorryError> {
+    monkey_business_with(input, 10_000, false, WorryMode::Residues)
+}
 
-    const ROUNDS: usize = 10_000;
+#[aoc(day11, part2, Cycles)]
+pub fn monkey_business_cycles(input: &[Monkey]) -> Result<u128, WorryError> {
+    cycles::monkey_business_after(input, 10_000, false)
+}
 
-    for _round in 0..ROUNDS {
-        for i in 0..monkeys.len() {
-            assert_ne!(i, monkeys[i].test.target_true);
-            assert_ne!(i, monkeys[i].test.target_false);
+/// Day 11: Monkey in the Middle
+pub struct Day11;
 
-            num_inspections[i] += monkeys[i].items.len();
+impl Solution for Day11 {
+    const DAY: u8 = DAY;
 
-            for j in 0..monkeys[i].items.len() {
-                let mut item = monkeys[i].items[j];
-                item.apply(&monkeys[i].operation);
-                item.relieve_modulo(modulo);
+    type Input = Vec<Monkey>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-                let target = monkeys[i].test.target(&item);
-                monkeys[target].items.push(item);
-            }
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_monkey_notes(input)
+    }
 
-            monkeys[i].items.clear();
-        }
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        monkey_business(input).map_err(|e| AocError::solve(DAY, e.to_string()))
     }
 
-    num_inspections.sort_unstable();
-    num_inspections[num_inspections.len() - 2..]
-        .iter()
-        .product()
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        monkey_business_no_relief(input).map_err(|e| AocError::solve(DAY, e.to_string()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use expr::BinOp;
 
-    const INPUT: &str = "\
+    pub(super) const INPUT: &str = "\
 Monkey 0:
   Starting items: 79, 98
   Operation: new = old * 19
@@ -235,7 +327,7 @@ Monkey 3:
         let expected_monkeys = vec![
             Monkey {
                 items: vec![Item { worry: 79 }, Item { worry: 98 }],
-                operation: Operation::Multiply(19),
+                operation: Expr::binary(Expr::Old, BinOp::Mul, Expr::Constant(19)),
                 test: Test {
                     divisible_by: 23,
                     target_true: 2,
@@ -249,7 +341,7 @@ Monkey 3:
                     Item { worry: 75 },
                     Item { worry: 74 },
                 ],
-                operation: Operation::Add(6),
+                operation: Expr::binary(Expr::Old, BinOp::Add, Expr::Constant(6)),
                 test: Test {
                     divisible_by: 19,
                     target_true: 2,
@@ -258,7 +350,7 @@ Monkey 3:
             },
             Monkey {
                 items: vec![Item { worry: 79 }, Item { worry: 60 }, Item { worry: 97 }],
-                operation: Operation::Square,
+                operation: Expr::binary(Expr::Old, BinOp::Mul, Expr::Old),
                 test: Test {
                     divisible_by: 13,
                     target_true: 1,
@@ -267,7 +359,7 @@ Monkey 3:
             },
             Monkey {
                 items: vec![Item { worry: 74 }],
-                operation: Operation::Add(3),
+                operation: Expr::binary(Expr::Old, BinOp::Add, Expr::Constant(3)),
                 test: Test {
                     divisible_by: 17,
                     target_true: 0,
@@ -276,20 +368,143 @@ Monkey 3:
             },
         ];
 
-        let monkeys = parse_monkey_notes(INPUT);
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
 
         assert_eq!(expected_monkeys, monkeys);
     }
 
     #[test]
     fn test_part_one() {
-        let monkeys = parse_monkey_notes(INPUT);
-        assert_eq!(monkey_business(&monkeys), 10605)
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        assert_eq!(monkey_business(&monkeys), Ok(10605))
     }
 
     #[test]
     fn test_part_two() {
-        let monkeys = parse_monkey_notes(INPUT);
-        assert_eq!(monkey_business_no_relief(&monkeys), 2713310158)
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        assert_eq!(monkey_business_no_relief(&monkeys), Ok(2713310158))
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = INPUT.replacen("new = old + 6", "new = old % 6", 1);
+        let error = parse_monkey_notes(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 11, line 10, column 24: unexpected character '%'"
+        );
+    }
+
+    #[test]
+    fn test_expression_operations() {
+        let input = INPUT
+            .replacen("new = old * 19", "new = (old - 1) * 19 / 2", 1)
+            .replacen("new = old + 3", "new = 3 + old", 1);
+        let monkeys = parse_monkey_notes(&input).unwrap();
+        assert_eq!(monkeys[0].operation.to_string(), "(old - 1) * 19 / 2");
+        assert_eq!(monkeys[3].operation.eval(74), Some(77));
+        assert_eq!(monkey_business(&monkeys), Ok(10812));
+    }
+
+    #[test]
+    fn test_worry_errors() {
+        let input = INPUT.replacen("new = old * 19", "new = old - 100", 1);
+        let monkeys = parse_monkey_notes(&input).unwrap();
+        assert_eq!(
+            Day11::part1(&monkeys).unwrap_err().to_string(),
+            "day 11: worry level went below zero: 79 - 100"
+        );
+        assert_eq!(
+            monkey_business_no_relief(&monkeys),
+            Err(WorryError::Unsupported("subtraction modulo the divisors"))
+        );
+        assert_eq!(
+            monkey_business_residues(&monkeys),
+            Err(WorryError::Unsupported("subtraction of residues"))
+        );
+        assert_eq!(
+            monkey_business_cycles(&monkeys),
+            Err(WorryError::Unsupported("subtraction modulo the divisors"))
+        );
+
+        let input = INPUT.replacen("new = old + 6", "new = old / 2", 1);
+        let monkeys = parse_monkey_notes(&input).unwrap();
+        assert!(monkey_business(&monkeys).is_ok());
+        assert_eq!(
+            monkey_business_no_relief(&monkeys),
+            Err(WorryError::Unsupported("division modulo the divisors"))
+        );
+        assert_eq!(
+            monkey_business_with(&monkeys, 20, true, WorryMode::Modular),
+            Err(WorryError::Unsupported("division modulo the divisors"))
+        );
+    }
+
+    #[test]
+    fn test_worry_modes() {
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        for mode in [
+            WorryMode::U128,
+            WorryMode::BigInt,
+            WorryMode::Residues,
+            WorryMode::Modular,
+        ] {
+            let result = monkey_business_with(&monkeys, 20, false, mode);
+            assert_eq!(result, Ok(10197), "{}", mode);
+        }
+        let overflow = monkey_business_with(&monkeys, 20, false, WorryMode::CheckedU64);
+        assert!(matches!(overflow, Err(WorryError::Overflow { .. })));
+
+        for mode in [WorryMode::CheckedU64, WorryMode::U128, WorryMode::BigInt] {
+            let result = monkey_business_with(&monkeys, 20, true, mode);
+            assert_eq!(result, Ok(10605), "{}", mode);
+        }
+        assert_eq!(
+            monkey_business_with(&monkeys, 20, true, WorryMode::Residues),
+            Err(WorryError::Unsupported("relief of residues"))
+        );
+        assert_eq!(
+            monkey_business_with(&monkeys, 10_000, false, WorryMode::Modular),
+            Ok(2713310158)
+        );
+
+        assert_eq!(monkey_business_residues(&monkeys), Ok(2713310158));
+    }
+
+    #[test]
+    fn test_invalid_monkeys() {
+        let input = INPUT
+            .replacen("throw to monkey 2", "throw to monkey 0", 1)
+            .replacen("throw to monkey 3", "throw to monkey 4", 1);
+        let error = parse_monkey_notes(&input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 11: invalid monkeys: monkey 0: throws if true to itself; \
+             monkey 0: throws if false to monkey 4, which doesn't exist"
+        );
+
+        let input = input.replacen("divisible by 13", "divisible by 15", 1);
+        match parse_monkey_notes_with_warnings(&input) {
+            Err(NotesError::Invalid(diagnostics)) => assert_eq!(diagnostics.len(), 3),
+            other => panic!("expected invalid monkeys, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_warnings() {
+        let input = INPUT.replacen("divisible by 13", "divisible by 15", 1);
+        let (monkeys, warnings) = parse_monkey_notes_with_warnings(&input).unwrap();
+        assert_eq!(monkeys.len(), 4);
+        assert_eq!(
+            warnings,
+            [Diagnostic::NonPrimeDivisor {
+                monkey: 2,
+                divisor: 15
+            }]
+        );
+        assert!(matches!(
+            parse_monkey_notes_with_warnings("Monkey 0:"),
+            Err(NotesError::Parse(AocError::UnexpectedEnd { .. }))
+        ));
     }
 }
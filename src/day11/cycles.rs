@@ -0,0 +1,128 @@
+//! Monkey business after any number of rounds, found by following each item until it repeats.
+//!
+//! Items never affect each other, so each can be followed on its own. An item's state at the start
+//! of a round is the monkey holding it and its worry level, and once a state repeats the item's
+//! inspections repeat too. Without relief, worry levels are kept modulo the product of the
+//! divisors so that there are finitely many states.
+
+use super::keep_away::{self, DivideBy, NoRelief, Relief};
+use super::worry::{Modular, Worry, WorryError};
+use super::Monkey;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// how many times each monkey inspects one item over `rounds` rounds, starting with `monkey`
+fn item_inspections<W: Worry + Eq + Hash, R: Relief<W>>(
+    monkeys: &[Monkey],
+    mut monkey: usize,
+    mut worry: W,
+    rounds: u64,
+    relief: &R,
+) -> Result<Vec<u64>, WorryError> {
+    let mut seen: HashMap<(usize, W), usize> = HashMap::new();
+    // the inspections by each monkey before each round
+    let mut history = vec![vec![0; monkeys.len()]];
+
+    for round in 0..rounds as usize {
+        if let Some(&start) = seen.get(&(monkey, worry.clone())) {
+            let (before, now) = (&history[start], &history[round]);
+            let cycle_len = (round - start) as u64;
+            let remaining = rounds - round as u64;
+            let partial = &history[start + (remaining % cycle_len) as usize];
+            let cycles = remaining / cycle_len;
+
+            let counts = (0..monkeys.len())
+                .map(|i| now[i] + cycles * (now[i] - before[i]) + (partial[i] - before[i]))
+                .collect();
+            return Ok(counts);
+        }
+        seen.insert((monkey, worry.clone()), round);
+
+        let mut counts = history[round].clone();
+        // items thrown to a later monkey are inspected again in the same round
+        loop {
+            counts[monkey] += 1;
+            let inspection = keep_away::inspect(&monkeys[monkey], monkey, worry, relief)?;
+            let same_round = inspection.target > monkey;
+            monkey = inspection.target;
+            worry = inspection.relieved;
+            if !same_round {
+                break;
+            }
+        }
+        history.push(counts);
+    }
+
+    Ok(history.pop().expect("history starts with the first round"))
+}
+
+fn total_inspections<W: Worry + Eq + Hash, R: Relief<W>>(
+    monkeys: &[Monkey],
+    rounds: u64,
+    relief: R,
+) -> Result<Vec<u64>, WorryError> {
+    let mut totals = vec![0; monkeys.len()];
+    for (i, items) in keep_away::starting_items::<W>(monkeys)?
+        .into_iter()
+        .enumerate()
+    {
+        for worry in items {
+            let counts = item_inspections(monkeys, i, worry, rounds, &relief)?;
+            for (total, count) in totals.iter_mut().zip(counts) {
+                *total += count;
+            }
+        }
+    }
+    Ok(totals)
+}
+
+/// the monkey business after `rounds` rounds, taking time proportional to the items' cycle
+/// lengths rather than `rounds`. relief divides worry levels by 3 after each inspection, in which
+/// case worry levels are exact and can overflow.
+pub fn monkey_business_after(
+    monkeys: &[Monkey],
+    rounds: u64,
+    relief: bool,
+) -> Result<u128, WorryError> {
+    let mut totals = if relief {
+        total_inspections::<u64, _>(monkeys, rounds, DivideBy(3))?
+    } else {
+        total_inspections::<Modular, _>(monkeys, rounds, NoRelief)?
+    };
+
+    totals.sort_unstable();
+    Ok(totals[totals.len().saturating_sub(2)..]
+        .iter()
+        .map(|&count| count as u128)
+        .product())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day11::worry::WorryMode;
+    use crate::day11::{monkey_business_with, parse_monkey_notes, tests::INPUT};
+
+    #[test]
+    fn test_matches_simulation() {
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        assert_eq!(monkey_business_after(&monkeys, 20, true), Ok(10605));
+        assert_eq!(
+            monkey_business_after(&monkeys, 10_000, false),
+            Ok(2713310158)
+        );
+
+        for rounds in [0, 1, 20, 1000, 1234] {
+            let simulated = monkey_business_with(&monkeys, rounds, false, WorryMode::Modular);
+            let after = monkey_business_after(&monkeys, rounds as u64, false);
+            assert_eq!(after, simulated.map(|business| business as u128));
+        }
+    }
+
+    #[test]
+    fn test_huge_round_count() {
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        let business = monkey_business_after(&monkeys, 1_000_000_000_000, false).unwrap();
+        assert_eq!(business, 27142382301385558311211320);
+    }
+}
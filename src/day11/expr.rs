@@ -0,0 +1,295 @@
+//! Expressions for a monkey's `new = ...` operation.
+//!
+//! Expressions combine `old` and constants with `+ - * /` and parentheses, where `*` and `/` bind
+//! tighter than `+` and `-` and operators of equal precedence group to the left.
+
+use crate::error::{parse_number, AocError};
+use std::fmt::{self, Display, Formatter};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+const DAY: u8 = 11;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinOp {
+    pub const ALL: [BinOp; 4] = [BinOp::Add, BinOp::Sub, BinOp::Mul, BinOp::Div];
+
+    fn precedence(self) -> u8 {
+        match self {
+            BinOp::Add | BinOp::Sub => 1,
+            BinOp::Mul | BinOp::Div => 2,
+        }
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            BinOp::Add => '+',
+            BinOp::Sub => '-',
+            BinOp::Mul => '*',
+            BinOp::Div => '/',
+        }
+    }
+
+    /// applies the operator, or returns `None` on overflow, underflow or division by zero
+    pub fn apply(self, lhs: usize, rhs: usize) -> Option<usize> {
+        match self {
+            BinOp::Add => lhs.checked_add(rhs),
+            BinOp::Sub => lhs.checked_sub(rhs),
+            BinOp::Mul => lhs.checked_mul(rhs),
+            BinOp::Div => lhs.checked_div(rhs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    Old,
+    Constant(usize),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+
+impl Expr {
+    pub fn binary(lhs: Expr, op: BinOp, rhs: Expr) -> Expr {
+        Expr::Binary(Box::new(lhs), op, Box::new(rhs))
+    }
+
+    /// parses an expression, with columns in errors counted from the start of `s`
+    pub fn parse(s: &str) -> Result<Expr, AocError> {
+        Expr::parse_from_column(s, 1)
+    }
+
+    /// parses an expression that starts at `first_column` of its line
+    pub fn parse_from_column(s: &str, first_column: usize) -> Result<Expr, AocError> {
+        let mut parser = Parser {
+            tokens: tokenize(s, first_column)?.into_iter().peekable(),
+            end: s.len() + first_column,
+        };
+        let expr = parser.expr()?;
+        match parser.tokens.next() {
+            None => Ok(expr),
+            Some((column, token)) => Err(AocError::parse(
+                DAY,
+                format!("unexpected {} after expression", token),
+            )
+            .at_column(column)),
+        }
+    }
+
+    /// evaluates the expression, or returns `None` on overflow, underflow or division by zero
+    pub fn eval(&self, old: usize) -> Option<usize> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Constant(value) => Some(*value),
+            Expr::Binary(lhs, op, rhs) => op.apply(lhs.eval(old)?, rhs.eval(old)?),
+        }
+    }
+
+    /// whether the expression uses `op` anywhere
+    pub fn uses(&self, op: BinOp) -> bool {
+        match self {
+            Expr::Old | Expr::Constant(_) => false,
+            Expr::Binary(lhs, o, rhs) => *o == op || lhs.uses(op) || rhs.uses(op),
+        }
+    }
+
+    fn fmt_with_precedence(&self, f: &mut Formatter<'_>, min_precedence: u8) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Constant(value) => write!(f, "{}", value),
+            Expr::Binary(lhs, op, rhs) => {
+                let precedence = op.precedence();
+                let parens = precedence < min_precedence;
+                if parens {
+                    write!(f, "(")?;
+                }
+                lhs.fmt_with_precedence(f, precedence)?;
+                write!(f, " {} ", op.symbol())?;
+                // operators group to the left, so a right operand of equal precedence needs parens
+                rhs.fmt_with_precedence(f, precedence + 1)?;
+                if parens {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// writes the expression with only the parentheses it needs
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Old,
+    Number(usize),
+    Op(BinOp),
+    Open,
+    Close,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Old => write!(f, "'old'"),
+            Token::Number(value) => write!(f, "'{}'", value),
+            Token::Op(op) => write!(f, "'{}'", op.symbol()),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+/// splits `s` into tokens, each with its column given `s` starts at `first_column`
+fn tokenize(s: &str, first_column: usize) -> Result<Vec<(usize, Token)>, AocError> {
+    let mut tokens = Vec::new();
+    let mut chars: Peekable<CharIndices> = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let column = i + first_column;
+        let token = match c {
+            ' ' => continue,
+            '+' => Token::Op(BinOp::Add),
+            '-' => Token::Op(BinOp::Sub),
+            '*' => Token::Op(BinOp::Mul),
+            '/' => Token::Op(BinOp::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            'a'..='z' | '0'..='9' => {
+                let mut end = i + c.len_utf8();
+                while let Some(&(j, c)) = chars.peek() {
+                    if !c.is_ascii_alphanumeric() {
+                        break;
+                    }
+                    end = j + c.len_utf8();
+                    chars.next();
+                }
+                match &s[i..end] {
+                    "old" => Token::Old,
+                    word if c.is_ascii_digit() => Token::Number(
+                        parse_number(DAY, word, "constant").map_err(|e| e.at_column(column))?,
+                    ),
+                    word => {
+                        return Err(AocError::parse(DAY, format!("unknown name '{}'", word))
+                            .at_column(column))
+                    }
+                }
+            }
+            _ => {
+                return Err(
+                    AocError::parse(DAY, format!("unexpected character '{}'", c)).at_column(column),
+                )
+            }
+        };
+        tokens.push((column, token));
+    }
+
+    Ok(tokens)
+}
+
+/// A precedence climbing parser over the tokens of one expression.
+struct Parser {
+    tokens: Peekable<std::vec::IntoIter<(usize, Token)>>,
+    /// the column just past the end, for errors about missing tokens
+    end: usize,
+}
+
+impl Parser {
+    fn expr(&mut self) -> Result<Expr, AocError> {
+        self.binary(1)
+    }
+
+    /// parses operands joined by operators of at least `min_precedence`
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, AocError> {
+        let mut lhs = self.operand()?;
+        while let Some(&(_, Token::Op(op))) = self.tokens.peek() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.tokens.next();
+            let rhs = self.binary(op.precedence() + 1)?;
+            lhs = Expr::binary(lhs, op, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn operand(&mut self) -> Result<Expr, AocError> {
+        let (column, token) = self
+            .tokens
+            .next()
+            .ok_or_else(|| AocError::unexpected_end(DAY, "operand").at_column(self.end))?;
+        match token {
+            Token::Old => Ok(Expr::Old),
+            Token::Number(value) => Ok(Expr::Constant(value)),
+            Token::Open => {
+                let expr = self.expr()?;
+                match self.tokens.next() {
+                    Some((_, Token::Close)) => Ok(expr),
+                    Some((column, token)) => Err(AocError::parse(
+                        DAY,
+                        format!("expected ')', found {}", token),
+                    )
+                    .at_column(column)),
+                    None => Err(AocError::unexpected_end(DAY, "')'").at_column(self.end)),
+                }
+            }
+            token => Err(
+                AocError::parse(DAY, format!("expected operand, found {}", token))
+                    .at_column(column),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence_and_parens() {
+        let expr = Expr::parse("old * (old + 2) - 10 / 3").unwrap();
+        assert_eq!(expr.eval(4), Some(21));
+        assert_eq!(expr.to_string(), "old * (old + 2) - 10 / 3");
+
+        let expr = Expr::parse("(100 - old) - (old - 1)").unwrap();
+        assert_eq!(expr.to_string(), "100 - old - (old - 1)");
+        assert_eq!(expr.eval(10), Some(81));
+        assert_eq!(expr.eval(101), None);
+
+        assert_eq!(Expr::parse("old / 0").unwrap().eval(1), None);
+        assert!(expr.uses(BinOp::Sub));
+        assert!(!expr.uses(BinOp::Div));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |s: &str| Expr::parse(s).unwrap_err().to_string();
+        assert_eq!(
+            error("old % 3"),
+            "day 11, column 5: unexpected character '%'"
+        );
+        assert_eq!(error("older + 1"), "day 11, column 1: unknown name 'older'");
+        assert_eq!(
+            error("(old + 1"),
+            "day 11, column 9: unexpected end of input, expected ')'"
+        );
+        assert_eq!(
+            error("old 3"),
+            "day 11, column 5: unexpected '3' after expression"
+        );
+        assert_eq!(
+            error("old * * 2"),
+            "day 11, column 7: expected operand, found '*'"
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! The game of keep away, a round at a time, with an optional log of every throw.
+
+use super::expr::{BinOp, Expr};
+use super::worry::{Worry, WorryError};
+use super::Monkey;
+use std::fmt::{Display, Write};
+
+/// How a monkey's worry level drops after it inspects an item without damaging it.
+pub trait Relief<W> {
+    fn relieve(&self, worry: W) -> Result<W, WorryError>;
+
+    /// the log line for relief that left the worry level at `worry`, if there is one
+    fn describe(&self, worry: &W) -> Option<String>
+    where
+        W: Display;
+}
+
+/// Divides worry levels, rounding down, as in part 1.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DivideBy(pub u64);
+
+impl<W: Worry> Relief<W> for DivideBy {
+    fn relieve(&self, worry: W) -> Result<W, WorryError> {
+        worry.relieve(self.0)
+    }
+
+    fn describe(&self, worry: &W) -> Option<String>
+    where
+        W: Display,
+    {
+        Some(format!(
+            "Monkey gets bored with item. Worry level is divided by {} to {}.",
+            self.0, worry
+        ))
+    }
+}
+
+/// Leaves worry levels alone, as in part 2, where they're kept small by their representation
+/// instead.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoRelief;
+
+impl<W> Relief<W> for NoRelief {
+    fn relieve(&self, worry: W) -> Result<W, WorryError> {
+        Ok(worry)
+    }
+
+    fn describe(&self, _: &W) -> Option<String>
+    where
+        W: Display,
+    {
+        None
+    }
+}
+
+/// One monkey inspecting and throwing one item.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Inspection<W> {
+    pub monkey: usize,
+    /// the worry level before inspection
+    pub worry: W,
+    /// the worry level after the monkey's operation
+    pub inspected: W,
+    /// the worry level after relief, which is the level the item is thrown with
+    pub relieved: W,
+    pub divisible: bool,
+    pub target: usize,
+}
+
+/// monkey number `index` inspecting an item with the worry level `worry`, then throwing it
+pub(super) fn inspect<W: Worry, R: Relief<W>>(
+    monkey: &Monkey,
+    index: usize,
+    worry: W,
+    relief: &R,
+) -> Result<Inspection<W>, WorryError> {
+    let inspected = monkey.operation.eval_worry(&worry)?;
+    let relieved = relief.relieve(inspected.clone())?;
+    let divisible = relieved.is_divisible(index, monkey.test.divisible_by as u64);
+    Ok(Inspection {
+        monkey: index,
+        worry,
+        inspected,
+        relieved,
+        divisible,
+        target: monkey.test.target_if(divisible),
+    })
+}
+
+/// the worry levels of the items each monkey starts with, as `W`, or an error if any monkey's
+/// operation uses something `W` doesn't support
+pub(super) fn starting_items<W: Worry>(monkeys: &[Monkey]) -> Result<Vec<Vec<W>>, WorryError> {
+    for op in BinOp::ALL {
+        if let Some(what) = W::unsupported(op) {
+            if monkeys.iter().any(|monkey| monkey.operation.uses(op)) {
+                return Err(WorryError::Unsupported(what));
+            }
+        }
+    }
+
+    let divisors = monkeys
+        .iter()
+        .map(|monkey| monkey.test.divisible_by as u64)
+        .collect::<Vec<_>>();
+    let context = W::context(&divisors)?;
+    monkeys
+        .iter()
+        .map(|monkey| {
+            monkey
+                .items
+                .iter()
+                .map(|item| W::new(item.worry as u64, &context))
+                .collect()
+        })
+        .collect()
+}
+
+/// Monkeys playing keep away, with worry levels held as `W` and relieved by `R` after every
+/// inspection.
+#[derive(Debug, Clone)]
+pub struct KeepAway<'a, W, R> {
+    monkeys: &'a [Monkey],
+    items: Vec<Vec<W>>,
+    relief: R,
+    num_inspections: Vec<usize>,
+    rounds: usize,
+}
+
+impl<'a, W: Worry, R: Relief<W>> KeepAway<'a, W, R> {
+    /// sets up a game, or fails if the starting worry levels can't be held as `W`
+    pub fn new(monkeys: &'a [Monkey], relief: R) -> Result<KeepAway<'a, W, R>, WorryError> {
+        Ok(KeepAway {
+            monkeys,
+            items: starting_items(monkeys)?,
+            relief,
+            num_inspections: vec![0; monkeys.len()],
+            rounds: 0,
+        })
+    }
+
+    /// how many rounds have been played
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// how many items each monkey has inspected
+    pub fn inspections(&self) -> &[usize] {
+        &self.num_inspections
+    }
+
+    /// the worry levels of the items `monkey` holds
+    pub fn items(&self, monkey: usize) -> &[W] {
+        &self.items[monkey]
+    }
+
+    /// plays one round, calling `observe` with every inspection. after an error the game can't be
+    /// played on, as the item being inspected is lost.
+    pub fn play_round_with<F: FnMut(&Inspection<W>)>(
+        &mut self,
+        mut observe: F,
+    ) -> Result<(), WorryError> {
+        for (i, monkey) in self.monkeys.iter().enumerate() {
+            let held = std::mem::take(&mut self.items[i]);
+            self.num_inspections[i] += held.len();
+
+            for worry in held {
+                let inspection = inspect(monkey, i, worry, &self.relief)?;
+                observe(&inspection);
+                self.items[inspection.target].push(inspection.relieved);
+            }
+        }
+        self.rounds += 1;
+        Ok(())
+    }
+
+    pub fn play_round(&mut self) -> Result<(), WorryError> {
+        self.play_round_with(|_| {})
+    }
+
+    /// plays one round, returning every inspection in order
+    pub fn play_round_logged(&mut self) -> Result<Vec<Inspection<W>>, WorryError> {
+        let mut log = Vec::new();
+        self.play_round_with(|inspection| log.push(inspection.clone()))?;
+        Ok(log)
+    }
+
+    pub fn play(&mut self, rounds: usize) -> Result<(), WorryError> {
+        for _round in 0..rounds {
+            self.play_round()?;
+        }
+        Ok(())
+    }
+
+    /// the product of the `top` highest inspection counts
+    pub fn monkey_business(&self, top: usize) -> usize {
+        let mut num_inspections = self.num_inspections.clone();
+        num_inspections.sort_unstable_by(|a, b| b.cmp(a));
+        num_inspections.iter().take(top).product()
+    }
+
+    /// describes inspections in the words of the puzzle's example
+    pub fn describe(&self, inspections: &[Inspection<W>]) -> String
+    where
+        W: Display,
+    {
+        let mut log = String::new();
+        let mut current = None;
+        for inspection in inspections {
+            let monkey = &self.monkeys[inspection.monkey];
+            if current != Some(inspection.monkey) {
+                current = Some(inspection.monkey);
+                writeln!(log, "Monkey {}:", inspection.monkey).unwrap();
+            }
+
+            writeln!(
+                log,
+                "  Monkey inspects an item with a worry level of {}.",
+                inspection.worry
+            )
+            .unwrap();
+            writeln!(
+                log,
+                "    {}",
+                describe_operation(&monkey.operation, &inspection.inspected)
+            )
+            .unwrap();
+            if let Some(relief) = self.relief.describe(&inspection.relieved) {
+                writeln!(log, "    {}", relief).unwrap();
+            }
+            let not = if inspection.divisible { "" } else { "not " };
+            writeln!(
+                log,
+                "    Current worry level is {}divisible by {}.",
+                not, monkey.test.divisible_by
+            )
+            .unwrap();
+            writeln!(
+                log,
+                "    Item with worry level {} is thrown to monkey {}.",
+                inspection.relieved, inspection.target
+            )
+            .unwrap();
+        }
+        log
+    }
+}
+
+/// the log line for an operation that gave `worry`, phrased as in the puzzle where it can be
+fn describe_operation(operation: &Expr, worry: impl Display) -> String {
+    let described = match operation {
+        Expr::Binary(lhs, op, rhs) if **lhs == Expr::Old => match (op, &**rhs) {
+            (BinOp::Mul, Expr::Old) => Some("is multiplied by itself".to_string()),
+            (BinOp::Mul, Expr::Constant(n)) => Some(format!("is multiplied by {}", n)),
+            (BinOp::Add, Expr::Constant(n)) => Some(format!("increases by {}", n)),
+            _ => None,
+        },
+        _ => None,
+    };
+    match described {
+        Some(described) => format!("Worry level {} to {}.", described, worry),
+        None => format!("Worry level becomes {} = {}.", operation, worry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day11::worry::Modular;
+    use crate::day11::{parse_monkey_notes, tests::INPUT};
+
+    #[test]
+    fn test_round_log() {
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        let mut game = KeepAway::<u64, _>::new(&monkeys, DivideBy(3)).unwrap();
+        let inspections = game.play_round_logged().unwrap();
+        let log = game.describe(&inspections);
+
+        let expected = "\
+Monkey 0:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by 19 to 1501.
+    Monkey gets bored with item. Worry level is divided by 3 to 500.
+    Current worry level is not divisible by 23.
+    Item with worry level 500 is thrown to monkey 3.
+  Monkey inspects an item with a worry level of 98.
+    Worry level is multiplied by 19 to 1862.
+    Monkey gets bored with item. Worry level is divided by 3 to 620.
+    Current worry level is not divisible by 23.
+    Item with worry level 620 is thrown to monkey 3.
+Monkey 1:
+  Monkey inspects an item with a worry level of 54.
+    Worry level increases by 6 to 60.
+    Monkey gets bored with item. Worry level is divided by 3 to 20.
+    Current worry level is not divisible by 19.
+    Item with worry level 20 is thrown to monkey 0.
+";
+        assert!(log.starts_with(expected), "{}", log);
+        assert!(log.contains("    Worry level is multiplied by itself to 6241.\n"));
+        assert_eq!(game.items(0), [20, 23, 27, 26]);
+        assert_eq!(game.inspections(), [2, 4, 3, 5]);
+    }
+
+    #[test]
+    fn test_round_log_without_relief() {
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        let mut game = KeepAway::<Modular, _>::new(&monkeys, NoRelief).unwrap();
+        let inspections = game.play_round_logged().unwrap();
+        let log = game.describe(&inspections);
+
+        let expected = "\
+Monkey 0:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by 19 to 1501.
+    Current worry level is not divisible by 23.
+    Item with worry level 1501 is thrown to monkey 3.
+";
+        assert!(log.starts_with(expected), "{}", log);
+        assert_eq!(game.inspections(), [2, 4, 3, 6]);
+    }
+
+    #[test]
+    fn test_top_k() {
+        let monkeys = parse_monkey_notes(INPUT).unwrap();
+        let mut game = KeepAway::<Modular, _>::new(&monkeys, NoRelief).unwrap();
+        game.play(1000).unwrap();
+        assert_eq!(game.inspections(), [5204, 4792, 199, 5192]);
+        assert_eq!(game.monkey_business(1), 5204);
+        assert_eq!(game.monkey_business(2), 5204 * 5192);
+        assert_eq!(game.rounds(), 1000);
+    }
+
+    #[test]
+    fn test_describe_expression() {
+        let operation = Expr::parse("(old - 1) * 2").unwrap();
+        assert_eq!(
+            describe_operation(&operation, 8),
+            "Worry level becomes (old - 1) * 2 = 8."
+        );
+    }
+}
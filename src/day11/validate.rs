@@ -0,0 +1,253 @@
+//! Checks of the monkeys' throwing graph, reporting every problem at once.
+
+use super::Monkey;
+use std::fmt::{self, Display, Formatter};
+
+/// Which way a monkey's test went.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Branch {
+    IfTrue,
+    IfFalse,
+}
+
+impl Display for Branch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Branch::IfTrue => write!(f, "if true"),
+            Branch::IfFalse => write!(f, "if false"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    /// the monkeys can still play, but some shortcuts may not hold
+    Warning,
+    /// the monkeys can't play
+    Error,
+}
+
+/// A problem with one monkey.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Diagnostic {
+    TargetOutOfRange {
+        monkey: usize,
+        branch: Branch,
+        target: usize,
+    },
+    SelfThrow {
+        monkey: usize,
+        branch: Branch,
+    },
+    /// a divisor of zero, which no worry level can be tested against
+    ZeroDivisor {
+        monkey: usize,
+    },
+    /// a divisor that isn't prime, so the product of the divisors isn't their least common
+    /// multiple
+    NonPrimeDivisor {
+        monkey: usize,
+        divisor: usize,
+    },
+    /// a divisor already used by monkey `first`
+    DuplicateDivisor {
+        monkey: usize,
+        first: usize,
+        divisor: usize,
+    },
+    /// a monkey that never holds an item
+    Unreachable {
+        monkey: usize,
+    },
+}
+
+impl Diagnostic {
+    pub fn monkey(&self) -> usize {
+        match *self {
+            Diagnostic::TargetOutOfRange { monkey, .. }
+            | Diagnostic::SelfThrow { monkey, .. }
+            | Diagnostic::NonPrimeDivisor { monkey, .. }
+            | Diagnostic::DuplicateDivisor { monkey, .. }
+            | Diagnostic::ZeroDivisor { monkey }
+            | Diagnostic::Unreachable { monkey } => monkey,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Diagnostic::TargetOutOfRange { .. }
+            | Diagnostic::SelfThrow { .. }
+            | Diagnostic::ZeroDivisor { .. } => Severity::Error,
+            Diagnostic::NonPrimeDivisor { .. }
+            | Diagnostic::DuplicateDivisor { .. }
+            | Diagnostic::Unreachable { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "monkey {}: ", self.monkey())?;
+        match self {
+            Diagnostic::TargetOutOfRange { branch, target, .. } => {
+                write!(
+                    f,
+                    "throws {} to monkey {}, which doesn't exist",
+                    branch, target
+                )
+            }
+            Diagnostic::SelfThrow { branch, .. } => write!(f, "throws {} to itself", branch),
+            Diagnostic::ZeroDivisor { .. } => write!(f, "divisor is 0"),
+            Diagnostic::NonPrimeDivisor { divisor, .. } => {
+                write!(f, "divisor {} is not prime", divisor)
+            }
+            Diagnostic::DuplicateDivisor { first, divisor, .. } => {
+                write!(f, "divisor {} is also used by monkey {}", divisor, first)
+            }
+            Diagnostic::Unreachable { .. } => write!(f, "never holds an item"),
+        }
+    }
+}
+
+fn is_prime(n: usize) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+/// every problem with `monkeys`, ordered by monkey
+pub fn validate(monkeys: &[Monkey]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for (i, monkey) in monkeys.iter().enumerate() {
+        let test = &monkey.test;
+        for (branch, target) in [
+            (Branch::IfTrue, test.target_true),
+            (Branch::IfFalse, test.target_false),
+        ] {
+            if target >= monkeys.len() {
+                diagnostics.push(Diagnostic::TargetOutOfRange {
+                    monkey: i,
+                    branch,
+                    target,
+                });
+            } else if target == i {
+                diagnostics.push(Diagnostic::SelfThrow { monkey: i, branch });
+            }
+        }
+
+        if test.divisible_by == 0 {
+            diagnostics.push(Diagnostic::ZeroDivisor { monkey: i });
+        } else if !is_prime(test.divisible_by) {
+            diagnostics.push(Diagnostic::NonPrimeDivisor {
+                monkey: i,
+                divisor: test.divisible_by,
+            });
+        }
+        if let Some(first) = monkeys[..i]
+            .iter()
+            .position(|m| m.test.divisible_by == test.divisible_by)
+        {
+            diagnostics.push(Diagnostic::DuplicateDivisor {
+                monkey: i,
+                first,
+                divisor: test.divisible_by,
+            });
+        }
+    }
+
+    // monkeys holding items at the start, and any they can throw to
+    let mut reachable: Vec<bool> = monkeys.iter().map(|m| !m.items.is_empty()).collect();
+    let mut stack: Vec<usize> = (0..monkeys.len()).filter(|&i| reachable[i]).collect();
+    while let Some(i) = stack.pop() {
+        for target in [monkeys[i].test.target_true, monkeys[i].test.target_false] {
+            if target < monkeys.len() && !reachable[target] {
+                reachable[target] = true;
+                stack.push(target);
+            }
+        }
+    }
+    for (i, _) in reachable.iter().enumerate().filter(|(_, &r)| !r) {
+        diagnostics.push(Diagnostic::Unreachable { monkey: i });
+    }
+
+    diagnostics.sort_by_key(Diagnostic::monkey);
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day11::{parse_monkey_notes, parse_monkey_notes_unchecked, tests::INPUT};
+
+    #[test]
+    fn test_valid() {
+        let monkeys = parse_monkey_notes_unchecked(INPUT).unwrap();
+        assert_eq!(validate(&monkeys), []);
+    }
+
+    #[test]
+    fn test_every_problem() {
+        let input = INPUT
+            .replacen(
+                "If true: throw to monkey 2",
+                "If true: throw to monkey 0",
+                1,
+            )
+            .replacen(
+                "If false: throw to monkey 0",
+                "If false: throw to monkey 7",
+                1,
+            )
+            .replacen("divisible by 13", "divisible by 23", 1)
+            .replacen("divisible by 17", "divisible by 15", 1)
+            .replacen("Starting items: 74", "Starting items:", 1)
+            // nothing throws to monkey 3, which now starts empty
+            .replacen(
+                "If true: throw to monkey 1",
+                "If true: throw to monkey 0",
+                1,
+            )
+            .replacen(
+                "If false: throw to monkey 3",
+                "If false: throw to monkey 1",
+                1,
+            )
+            .replacen(
+                "If false: throw to monkey 3",
+                "If false: throw to monkey 1",
+                1,
+            );
+        let monkeys = parse_monkey_notes_unchecked(&input).unwrap();
+
+        let diagnostics = validate(&monkeys);
+        let messages = diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "monkey 0: throws if true to itself",
+                "monkey 1: throws if false to monkey 7, which doesn't exist",
+                "monkey 2: divisor 23 is also used by monkey 0",
+                "monkey 3: divisor 15 is not prime",
+                "monkey 3: never holds an item",
+            ]
+        );
+        assert_eq!(diagnostics[0].severity(), Severity::Error);
+        assert_eq!(diagnostics[4].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn test_zero_divisor() {
+        let input = INPUT.replacen("divisible by 19", "divisible by 0", 1);
+        let monkeys = parse_monkey_notes_unchecked(&input).unwrap();
+
+        let diagnostics = validate(&monkeys);
+        assert_eq!(diagnostics, [Diagnostic::ZeroDivisor { monkey: 1 }]);
+        assert_eq!(diagnostics[0].severity(), Severity::Error);
+        assert!(parse_monkey_notes(&input).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! Representations of worry levels, for comparing ways of keeping them from overflowing.
+
+use super::expr::{BinOp, Expr};
+use num_bigint::BigUint;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// Why a worry level couldn't be updated.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum WorryError {
+    Overflow {
+        lhs: String,
+        op: BinOp,
+        rhs: String,
+    },
+    Underflow {
+        lhs: String,
+        rhs: String,
+    },
+    DivisionByZero,
+    /// the divisors' product, which the representation keeps worry levels modulo, doesn't fit
+    DivisorsOverflow,
+    /// the representation can't carry out the operation
+    Unsupported(&'static str),
+}
+
+impl Display for WorryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WorryError::Overflow { lhs, op, rhs } => {
+                write!(f, "worry level overflowed: {} {} {}", lhs, op.symbol(), rhs)
+            }
+            WorryError::Underflow { lhs, rhs } => {
+                write!(f, "worry level went below zero: {} - {}", lhs, rhs)
+            }
+            WorryError::DivisionByZero => write!(f, "worry level divided by zero"),
+            WorryError::DivisorsOverflow => write!(f, "product of the divisors overflows u64"),
+            WorryError::Unsupported(what) => write!(f, "unsupported worry operation: {}", what),
+        }
+    }
+}
+
+impl Error for WorryError {}
+
+/// A worry level, in some representation.
+pub trait Worry: Clone {
+    /// what every worry level in a game needs to know about the monkeys' tests
+    type Context;
+
+    /// works out the context once per game. `divisors` holds each monkey's test divisor, in order.
+    fn context(divisors: &[u64]) -> Result<Self::Context, WorryError>;
+
+    /// converts a starting worry level
+    fn new(value: u64, context: &Self::Context) -> Result<Self, WorryError>;
+
+    /// a constant in the same representation as `self`
+    fn constant(&self, value: u64) -> Self;
+
+    /// why `op` can't be used in this representation, if it can't. checked before a game starts,
+    /// as some operations only go wrong once a true value would go below zero.
+    fn unsupported(_op: BinOp) -> Option<&'static str> {
+        None
+    }
+
+    fn combine(&self, op: BinOp, rhs: &Self) -> Result<Self, WorryError>;
+
+    /// divides by `divisor`, rounding down
+    fn relieve(&self, divisor: u64) -> Result<Self, WorryError>;
+
+    /// whether the worry level passes `monkey`'s test of divisibility by `divisor`
+    fn is_divisible(&self, monkey: usize, divisor: u64) -> bool;
+}
+
+impl Expr {
+    /// evaluates the expression in any worry representation
+    pub fn eval_worry<W: Worry>(&self, old: &W) -> Result<W, WorryError> {
+        match self {
+            Expr::Old => Ok(old.clone()),
+            Expr::Constant(value) => Ok(old.constant(*value as u64)),
+            Expr::Binary(lhs, op, rhs) => lhs.eval_worry(old)?.combine(*op, &rhs.eval_worry(old)?),
+        }
+    }
+}
+
+macro_rules! impl_checked_worry {
+    ($t:ty) => {
+        impl Worry for $t {
+            type Context = ();
+
+            fn context(_: &[u64]) -> Result<(), WorryError> {
+                Ok(())
+            }
+
+            fn new(value: u64, _: &()) -> Result<Self, WorryError> {
+                Ok(value.into())
+            }
+
+            fn constant(&self, value: u64) -> Self {
+                value.into()
+            }
+
+            fn combine(&self, op: BinOp, rhs: &Self) -> Result<Self, WorryError> {
+                let result = match op {
+                    BinOp::Add => self.checked_add(*rhs),
+                    BinOp::Sub => {
+                        return self.checked_sub(*rhs).ok_or_else(|| WorryError::Underflow {
+                            lhs: self.to_string(),
+                            rhs: rhs.to_string(),
+                        })
+                    }
+                    BinOp::Mul => self.checked_mul(*rhs),
+                    BinOp::Div => return self.checked_div(*rhs).ok_or(WorryError::DivisionByZero),
+                };
+                result.ok_or_else(|| WorryError::Overflow {
+                    lhs: self.to_string(),
+                    op,
+                    rhs: rhs.to_string(),
+                })
+            }
+
+            fn relieve(&self, divisor: u64) -> Result<Self, WorryError> {
+                self.checked_div(divisor.into())
+                    .ok_or(WorryError::DivisionByZero)
+            }
+
+            fn is_divisible(&self, _: usize, divisor: u64) -> bool {
+                self % <$t>::from(divisor) == 0
+            }
+        }
+    };
+}
+
+impl_checked_worry!(u64);
+impl_checked_worry!(u128);
+
+impl Worry for BigUint {
+    type Context = ();
+
+    fn context(_: &[u64]) -> Result<(), WorryError> {
+        Ok(())
+    }
+
+    fn new(value: u64, _: &()) -> Result<Self, WorryError> {
+        Ok(value.into())
+    }
+
+    fn constant(&self, value: u64) -> Self {
+        value.into()
+    }
+
+    fn combine(&self, op: BinOp, rhs: &Self) -> Result<Self, WorryError> {
+        match op {
+            BinOp::Add => Ok(self + rhs),
+            BinOp::Sub if self < rhs => Err(WorryError::Underflow {
+                lhs: self.to_string(),
+                rhs: rhs.to_string(),
+            }),
+            BinOp::Sub => Ok(self - rhs),
+            BinOp::Mul => Ok(self * rhs),
+            BinOp::Div if *rhs == BigUint::ZERO => Err(WorryError::DivisionByZero),
+            BinOp::Div => Ok(self / rhs),
+        }
+    }
+
+    fn relieve(&self, divisor: u64) -> Result<Self, WorryError> {
+        if divisor == 0 {
+            return Err(WorryError::DivisionByZero);
+        }
+        Ok(self / divisor)
+    }
+
+    fn is_divisible(&self, _: usize, divisor: u64) -> bool {
+        self % divisor == BigUint::ZERO
+    }
+}
+
+/// A worry level stored as its residue modulo each monkey's divisor, which is all the tests need
+/// and never grows. Division has no equivalent, so relief and `/` are unsupported.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Residues {
+    moduli: Rc<[u64]>,
+    residues: Vec<u64>,
+}
+
+impl Residues {
+    pub fn residues(&self) -> &[u64] {
+        &self.residues
+    }
+
+    fn from_value(value: u64, moduli: &Rc<[u64]>) -> Self {
+        Residues {
+            moduli: moduli.clone(),
+            residues: moduli.iter().map(|m| value % m).collect(),
+        }
+    }
+
+    fn zip_with<F: Fn(u64, u64, u64) -> u128>(&self, rhs: &Self, f: F) -> Self {
+        let residues = self
+            .moduli
+            .iter()
+            .zip(self.residues.iter().zip(&rhs.residues))
+            .map(|(&m, (&a, &b))| (f(a, b, m) % m as u128) as u64)
+            .collect();
+        Residues {
+            moduli: self.moduli.clone(),
+            residues,
+        }
+    }
+}
+
+impl Worry for Residues {
+    type Context = Rc<[u64]>;
+
+    fn context(divisors: &[u64]) -> Result<Rc<[u64]>, WorryError> {
+        if divisors.contains(&0) {
+            return Err(WorryError::DivisionByZero);
+        }
+        Ok(divisors.into())
+    }
+
+    fn new(value: u64, moduli: &Rc<[u64]>) -> Result<Self, WorryError> {
+        Ok(Residues::from_value(value, moduli))
+    }
+
+    fn constant(&self, value: u64) -> Self {
+        Residues::from_value(value, &self.moduli)
+    }
+
+    fn unsupported(op: BinOp) -> Option<&'static str> {
+        match op {
+            BinOp::Add | BinOp::Mul => None,
+            // residues can't tell if the true value went below zero
+            BinOp::Sub => Some("subtraction of residues"),
+            BinOp::Div => Some("division of residues"),
+        }
+    }
+
+    fn combine(&self, op: BinOp, rhs: &Self) -> Result<Self, WorryError> {
+        match op {
+            BinOp::Add => Ok(self.zip_with(rhs, |a, b, _| a as u128 + b as u128)),
+            // only reached if a game skipped `unsupported`, so this assumes no underflow
+            BinOp::Sub => Ok(self.zip_with(rhs, |a, b, m| a as u128 + (m - b) as u128)),
+            BinOp::Mul => Ok(self.zip_with(rhs, |a, b, _| a as u128 * b as u128)),
+            BinOp::Div => Err(WorryError::Unsupported("division of residues")),
+        }
+    }
+
+    fn relieve(&self, _: u64) -> Result<Self, WorryError> {
+        Err(WorryError::Unsupported("relief of residues"))
+    }
+
+    fn is_divisible(&self, monkey: usize, _: u64) -> bool {
+        self.residues[monkey] == 0
+    }
+}
+
+/// A worry level stored modulo the product of every monkey's divisor, the usual trick for part 2.
+/// Like [`Residues`] it can't be divided, but it's a single number.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Modular {
+    modulus: u64,
+    value: u64,
+}
+
+impl Modular {
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    fn with_value(&self, value: u128) -> Self {
+        Modular {
+            modulus: self.modulus,
+            value: (value % self.modulus as u128) as u64,
+        }
+    }
+}
+
+impl Display for Modular {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Worry for Modular {
+    /// the product of the divisors
+    type Context = u64;
+
+    fn context(divisors: &[u64]) -> Result<u64, WorryError> {
+        let modulus = divisors
+            .iter()
+            .try_fold(1u64, |product, &divisor| product.checked_mul(divisor))
+            .ok_or(WorryError::DivisorsOverflow)?;
+        if modulus == 0 {
+            return Err(WorryError::DivisionByZero);
+        }
+        Ok(modulus)
+    }
+
+    fn new(value: u64, &modulus: &u64) -> Result<Self, WorryError> {
+        Ok(Modular {
+            modulus,
+            value: value % modulus,
+        })
+    }
+
+    fn constant(&self, value: u64) -> Self {
+        self.with_value(value.into())
+    }
+
+    fn unsupported(op: BinOp) -> Option<&'static str> {
+        match op {
+            BinOp::Add | BinOp::Mul => None,
+            BinOp::Sub => Some("subtraction modulo the divisors"),
+            BinOp::Div => Some("division modulo the divisors"),
+        }
+    }
+
+    fn combine(&self, op: BinOp, rhs: &Self) -> Result<Self, WorryError> {
+        let (a, b, m) = (self.value as u128, rhs.value as u128, self.modulus as u128);
+        match op {
+            BinOp::Add => Ok(self.with_value(a + b)),
+            // as with residues, this assumes the true value didn't go below zero
+            BinOp::Sub => Ok(self.with_value(a + m - b)),
+            BinOp::Mul => Ok(self.with_value(a * b)),
+            BinOp::Div => Err(WorryError::Unsupported("division modulo the divisors")),
+        }
+    }
+
+    fn relieve(&self, _: u64) -> Result<Self, WorryError> {
+        Err(WorryError::Unsupported("relief modulo the divisors"))
+    }
+
+    fn is_divisible(&self, _: usize, divisor: u64) -> bool {
+        self.value.is_multiple_of(divisor)
+    }
+}
+
+/// Which [`Worry`] representation to simulate with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum WorryMode {
+    /// `u64`, failing on overflow
+    #[default]
+    CheckedU64,
+    /// `u128`, failing on overflow
+    U128,
+    /// arbitrary precision, which never overflows but grows without bound
+    BigInt,
+    /// [`Residues`], which never grow but can't be divided
+    Residues,
+    /// [`Modular`], which never grows but can't be divided
+    Modular,
+}
+
+impl WorryMode {
+    pub const ALL: [WorryMode; 5] = [
+        WorryMode::CheckedU64,
+        WorryMode::U128,
+        WorryMode::BigInt,
+        WorryMode::Residues,
+        WorryMode::Modular,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WorryMode::CheckedU64 => "u64",
+            WorryMode::U128 => "u128",
+            WorryMode::BigInt => "bigint",
+            WorryMode::Residues => "residues",
+            WorryMode::Modular => "modulo",
+        }
+    }
+}
+
+impl FromStr for WorryMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WorryMode::ALL
+            .into_iter()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| format!("unknown worry mode '{}'", s))
+    }
+}
+
+impl Display for WorryMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_overflow() {
+        let expr = Expr::parse("old * old").unwrap();
+        let big = u64::new(1 << 32, &()).unwrap();
+        assert_eq!(
+            expr.eval_worry(&big).unwrap_err().to_string(),
+            "worry level overflowed: 4294967296 * 4294967296"
+        );
+        assert_eq!(expr.eval_worry(&u128::from(1u64 << 32)), Ok(1 << 64));
+        assert_eq!(
+            expr.eval_worry(&BigUint::from(1u64 << 32)),
+            Ok(BigUint::from(1u128 << 64))
+        );
+    }
+
+    #[test]
+    fn test_residues() {
+        let moduli = Residues::context(&[3, 5, 7]).unwrap();
+        // (10 - 4) * 10 + 3 = 63
+        let expr = Expr::parse("(old - 4) * old + 3").unwrap();
+        let worry = expr
+            .eval_worry(&Residues::new(10, &moduli).unwrap())
+            .unwrap();
+        assert_eq!(worry.residues(), [0, 3, 0]);
+        assert!(worry.is_divisible(0, 3));
+        assert!(!worry.is_divisible(1, 5));
+        assert_eq!(
+            worry.relieve(3),
+            Err(WorryError::Unsupported("relief of residues"))
+        );
+    }
+
+    #[test]
+    fn test_modulus_overflow() {
+        let primes = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+        assert_eq!(Modular::context(&primes[..15]), Ok(614889782588491410));
+        assert_eq!(Modular::context(&primes), Err(WorryError::DivisorsOverflow));
+        // residues only ever hold one divisor's worth
+        assert!(Residues::context(&primes).is_ok());
+    }
+}
@@ -1,231 +1,203 @@
+use crate::error::AocError;
+use crate::grid::{Grid, Position};
+use crate::search::{self, Graph, Path};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+
+const DAY: u8 = 12;
 
 pub struct Heightmap {
-    heights: Vec<u8>,
-    num_cols: usize,
-    start: (usize, usize),
-    end: (usize, usize),
+    heights: Grid<u8>,
+    start: Position,
+    end: Position,
 }
 
 #[aoc_generator(day12)]
-pub fn parse_heightmap(input: &str) -> Heightmap {
-    let num_cols = input
-        .lines()
-        .next()
-        .expect("must have at least one row")
-        .len();
-
-    let mut start: Option<(usize, usize)> = None;
-    let mut end: Option<(usize, usize)> = None;
-
-    let mut heights = Vec::with_capacity(input.len());
-    for (row, line) in input.lines().enumerate() {
-        for (col, char) in line.chars().enumerate() {
-            match char {
-                'a'..='z' => {
-                    heights.push(char as u8 - 'a' as u8 + 1);
-                }
-                'S' => {
-                    assert_eq!(start, None, "multiple 'S' chars");
-                    start = Some((row, col));
-                    heights.push(1) // S has height 'a'
-                }
-                'E' => {
-                    assert_eq!(end, None, "multiple 'E' chars");
-                    end = Some((row, col));
-                    heights.push(26) // E has height 'z'
-                }
-                _ => panic!("unexpected character '{}'", char),
+pub fn parse_heightmap(input: &str) -> Result<Heightmap, AocError> {
+    let mut start: Option<Position> = None;
+    let mut end: Option<Position> = None;
+
+    let heights = Grid::parse_char_map(DAY, input, |position, char| match char {
+        'a'..='z' => Ok(char as u8 - b'a' + 1),
+        'S' => {
+            if start.is_some() {
+                return Err(AocError::parse(DAY, "multiple 'S' chars"));
             }
+            start = Some(position);
+            Ok(1) // S has height 'a'
         }
-    }
+        'E' => {
+            if end.is_some() {
+                return Err(AocError::parse(DAY, "multiple 'E' chars"));
+            }
+            end = Some(position);
+            Ok(26) // E has height 'z'
+        }
+        _ => Err(AocError::parse(
+            DAY,
+            format!("unexpected character '{}'", char),
+        )),
+    })?;
 
-    Heightmap {
+    Ok(Heightmap {
         heights,
-        num_cols,
-        start: start.expect("no 'S' char found"),
-        end: end.expect("no 'E' char found"),
-    }
+        start: start.ok_or_else(|| AocError::parse(DAY, "no 'S' char found"))?,
+        end: end.ok_or_else(|| AocError::parse(DAY, "no 'E' char found"))?,
+    })
 }
 
+/// What a step costs, by whether it climbs, stays level or descends. Steps can climb at most one
+/// level, but descend any number for the same cost.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+pub struct StepCosts {
+    pub up: u64,
+    pub level: u64,
+    pub down: u64,
 }
 
-fn build_bfs_route_to_start(
-    heights: &Vec<u8>,
-    num_rows: usize,
-    num_cols: usize,
-    start: (usize, usize),
-    end: (usize, usize),
-) -> Vec<(usize, usize)> {
-    let mut parents: Vec<Option<Direction>> = vec![None; heights.len()];
-    let mut queue = VecDeque::from([end]);
-
-    let index = |row: usize, col: usize| -> usize { row * num_cols + col };
-
-    while let Some((row, col)) = queue.pop_front() {
-        if start == (row, col) {
-            break;
-        }
-
-        let height = heights[index(row, col)];
+impl StepCosts {
+    /// every step costs the same, as in the puzzle
+    pub const UNIT: StepCosts = StepCosts {
+        up: 1,
+        level: 1,
+        down: 1,
+    };
 
-        if col > 0 {
-            let left = index(row, col - 1);
-            if parents[left] == None && height <= heights[left] + 1 {
-                parents[left] = Some(Direction::Right);
-                queue.push_back((row, col - 1));
-            }
-        }
-
-        if col + 1 < num_cols {
-            let right = index(row, col + 1);
-            if parents[right] == None && height <= heights[right] + 1 {
-                parents[right] = Some(Direction::Left);
-                queue.push_back((row, col + 1));
-            }
-        }
-
-        if row > 0 {
-            let up = index(row - 1, col);
-            if parents[up] == None && height <= heights[up] + 1 {
-                parents[up] = Some(Direction::Down);
-                queue.push_back((row - 1, col));
-            }
-        }
-
-        if row + 1 < num_rows {
-            let down = index(row + 1, col);
-            if parents[down] == None && height <= heights[down] + 1 {
-                parents[down] = Some(Direction::Up);
-                queue.push_back((row + 1, col));
-            }
+    fn cost(&self, from: u8, to: u8) -> u64 {
+        match to.cmp(&from) {
+            Ordering::Greater => self.up,
+            Ordering::Equal => self.level,
+            Ordering::Less => self.down,
         }
     }
 
-    build_route(num_rows, num_cols, start, end, &parents)
+    fn min(&self) -> u64 {
+        self.up.min(self.level).min(self.down)
+    }
 }
 
-fn build_bfs_route_to_a(
-    heights: &Vec<u8>,
-    num_rows: usize,
-    num_cols: usize,
-    end: (usize, usize),
-) -> Vec<(usize, usize)> {
-    let mut parents: Vec<Option<Direction>> = vec![None; heights.len()];
-    let mut queue = VecDeque::from([end]);
-    let mut start: Option<(usize, usize)> = None;
-
-    let index = |row: usize, col: usize| -> usize { row * num_cols + col };
-
-    while let Some((row, col)) = queue.pop_front() {
-        let height = heights[index(row, col)];
-        if height == 1 {
-            start = Some((row, col));
-            break;
-        }
+/// The heightmap as a graph of the steps the puzzle allows. Reversed, every step goes the other
+/// way (still costed as it would be going forwards), for searching back from the end.
+struct Climb<'a> {
+    heights: &'a Grid<u8>,
+    costs: StepCosts,
+    reversed: bool,
+}
 
-        if col > 0 {
-            let left = index(row, col - 1);
-            if parents[left] == None && height <= heights[left] + 1 {
-                parents[left] = Some(Direction::Right);
-                queue.push_back((row, col - 1));
-            }
-        }
+impl Graph for Climb<'_> {
+    type Node = Position;
 
-        if col + 1 < num_cols {
-            let right = index(row, col + 1);
-            if parents[right] == None && height <= heights[right] + 1 {
-                parents[right] = Some(Direction::Left);
-                queue.push_back((row, col + 1));
-            }
-        }
+    fn neighbours(&self, position: Position) -> impl Iterator<Item = (Position, u64)> {
+        self.heights.neighbours4(position).filter_map(move |next| {
+            let (from, to) = if self.reversed {
+                (self.heights[next], self.heights[position])
+            } else {
+                (self.heights[position], self.heights[next])
+            };
+            (to <= from + 1).then(|| (next, self.costs.cost(from, to)))
+        })
+    }
+}
 
-        if row > 0 {
-            let up = index(row - 1, col);
-            if parents[up] == None && height <= heights[up] + 1 {
-                parents[up] = Some(Direction::Down);
-                queue.push_back((row - 1, col));
-            }
+impl Heightmap {
+    fn climb(&self, costs: StepCosts, reversed: bool) -> Climb<'_> {
+        Climb {
+            heights: &self.heights,
+            costs,
+            reversed,
         }
+    }
 
-        if row + 1 < num_rows {
-            let down = index(row + 1, col);
-            if parents[down] == None && height <= heights[down] + 1 {
-                parents[down] = Some(Direction::Up);
-                queue.push_back((row + 1, col));
-            }
-        }
+    /// the route from the start to the end with the fewest steps
+    pub fn shortest_route_from_start(&self) -> Option<Path<Position>> {
+        search::bfs(&self.climb(StepCosts::UNIT, false), self.start, |p| {
+            p == self.end
+        })
     }
 
-    let start = start.expect("did not find path to any square with height 'a'");
-    build_route(num_rows, num_cols, start, end, &parents)
-}
+    /// the route with the fewest steps to the end from any square of height 'a'
+    pub fn shortest_route_from_any_a(&self) -> Option<Path<Position>> {
+        let path = search::bfs(&self.climb(StepCosts::UNIT, true), self.end, |p| {
+            self.heights[p] == 1
+        });
+        path.map(reversed)
+    }
 
-fn build_route(
-    num_rows: usize,
-    num_cols: usize,
-    start: (usize, usize),
-    end: (usize, usize),
-    parents: &[Option<Direction>],
-) -> Vec<(usize, usize)> {
-    let index = |row: usize, col: usize| -> usize { row * num_cols + col };
-
-    let mut route = vec![start];
-    let mut current = start;
-    while current != end {
-        let (row, col) = current;
-        current = match parents[index(row, col)] {
-            None => panic!("did not find path to end"),
-            Some(Direction::Left) => {
-                assert!(col > 0);
-                (row, col - 1)
-            }
-            Some(Direction::Right) => {
-                assert!(col + 1 < num_cols);
-                (row, col + 1)
-            }
-            Some(Direction::Up) => {
-                assert!(row > 0);
-                (row - 1, col)
-            }
-            Some(Direction::Down) => {
-                assert!(row + 1 < num_rows);
-                (row + 1, col)
-            }
+    /// the cheapest route from the start to the end, found with A*
+    pub fn cheapest_route_from_start(&self, costs: StepCosts) -> Option<Path<Position>> {
+        let end_height = self.heights[self.end];
+        let (end_row, end_col) = self.end;
+        // every level still to climb takes a climbing step, and any other steps left cost at
+        // least the cheapest step
+        let heuristic = |(row, col): Position| {
+            let climbs = end_height.saturating_sub(self.heights[(row, col)]) as u64;
+            let distance = (row.abs_diff(end_row) + col.abs_diff(end_col)) as u64;
+            climbs * costs.up + distance.saturating_sub(climbs) * costs.min()
         };
-        route.push(current);
+        search::a_star(
+            &self.climb(costs, false),
+            self.start,
+            |p| p == self.end,
+            heuristic,
+        )
     }
-    route
+
+    /// the cheapest route to the end from any square of height 'a', found with Dijkstra's
+    /// algorithm
+    pub fn cheapest_route_from_any_a(&self, costs: StepCosts) -> Option<Path<Position>> {
+        let path = search::dijkstra(&self.climb(costs, true), self.end, |p| self.heights[p] == 1);
+        path.map(reversed)
+    }
+}
+
+/// turns a path found by searching backwards around
+fn reversed(mut path: Path<Position>) -> Path<Position> {
+    path.nodes.reverse();
+    path
 }
 
 #[aoc(day12, part1)]
 pub fn len_shortest_path_from_start(heightmap: &Heightmap) -> usize {
-    let route = build_bfs_route_to_start(
-        &heightmap.heights,
-        &heightmap.heights.len() / heightmap.num_cols,
-        heightmap.num_cols,
-        heightmap.start,
-        heightmap.end,
-    );
-    route.len() - 1 // num steps, not num nodes
+    let route = heightmap.shortest_route_from_start();
+    route.expect("did not find path to end").steps()
+}
+
+#[aoc(day12, part1, AStar)]
+pub fn len_shortest_path_from_start_a_star(heightmap: &Heightmap) -> usize {
+    let route = heightmap.cheapest_route_from_start(StepCosts::UNIT);
+    route.expect("did not find path to end").steps()
 }
 
 #[aoc(day12, part2)]
 pub fn len_shortest_path_from_any_a(heightmap: &Heightmap) -> usize {
-    let route = build_bfs_route_to_a(
-        &heightmap.heights,
-        &heightmap.heights.len() / heightmap.num_cols,
-        heightmap.num_cols,
-        heightmap.end,
-    );
-    route.len() - 1 // num steps, not num nodes
+    let route = heightmap.shortest_route_from_any_a();
+    route
+        .expect("did not find path to any square with height 'a'")
+        .steps()
+}
+
+/// Day 12: Hill Climbing Algorithm
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = DAY;
+
+    type Input = Heightmap;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_heightmap(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(len_shortest_path_from_start(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(len_shortest_path_from_any_a(input))
+    }
 }
 
 #[cfg(test)]
@@ -241,13 +213,46 @@ abdefghi";
 
     #[test]
     fn test_part_one() {
-        let heightmap = parse_heightmap(INPUT);
+        let heightmap = parse_heightmap(INPUT).unwrap();
         assert_eq!(len_shortest_path_from_start(&heightmap), 31);
     }
 
     #[test]
     fn test_part_two() {
-        let heightmap = parse_heightmap(INPUT);
+        let heightmap = parse_heightmap(INPUT).unwrap();
         assert_eq!(len_shortest_path_from_any_a(&heightmap), 29);
     }
+
+    #[test]
+    fn test_climb_costs() {
+        let heightmap = parse_heightmap(INPUT).unwrap();
+
+        let cheapest = heightmap
+            .cheapest_route_from_start(StepCosts::UNIT)
+            .unwrap();
+        assert_eq!((cheapest.cost, cheapest.steps()), (31, 31));
+        let cheapest = heightmap
+            .cheapest_route_from_any_a(StepCosts::UNIT)
+            .unwrap();
+        assert_eq!(cheapest.cost, 29);
+        assert_eq!(cheapest.goal(), heightmap.end);
+
+        let costs = StepCosts {
+            up: 5,
+            level: 2,
+            down: 1,
+        };
+        let cheapest = heightmap.cheapest_route_from_start(costs).unwrap();
+        let reference = search::dijkstra(&heightmap.climb(costs, false), heightmap.start, |p| {
+            p == heightmap.end
+        });
+        assert_eq!(Some(cheapest.cost), reference.map(|path| path.cost));
+
+        let cost: u64 = cheapest
+            .nodes
+            .windows(2)
+            .map(|step| costs.cost(heightmap.heights[step[0]], heightmap.heights[step[1]]))
+            .sum();
+        assert_eq!(cost, cheapest.cost);
+    }
 }
@@ -1,10 +1,14 @@
+use crate::error::AocError;
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::iter::Peekable;
-use std::str::Chars;
+use std::str::CharIndices;
+
+const DAY: u8 = 13;
 
 #[derive(Debug, PartialOrd, PartialEq, Clone)]
-enum Packet {
+pub enum Packet {
     Value(PacketValue),
     List(PacketList),
 }
@@ -23,25 +27,50 @@ impl Packet {
             }
         }
     }
+
+    /// parses a line containing a single packet list
+    fn parse_line(line: &str) -> Result<Packet, AocError> {
+        let mut s = line.char_indices().peekable();
+        let list = PacketList::parse(&mut s)?;
+
+        match s.next() {
+            None => Ok(Packet::List(list)),
+            Some((i, c)) => Err(AocError::parse(
+                DAY,
+                format!("unexpected '{}' after end of packet", c),
+            )
+            .at_column(i + 1)),
+        }
+    }
 }
 
 #[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
-struct PacketValue(u8);
+pub struct PacketValue(u8);
 
 impl PacketValue {
-    fn parse(s: &mut Peekable<Chars>) -> PacketValue {
-        let mut value = s.next().and_then(|c| c.to_digit(10)).expect("not a number") as u8;
-        while let Some(c) = s.peek() {
+    fn parse(s: &mut Peekable<CharIndices>) -> Result<PacketValue, AocError> {
+        let (start, c) = s
+            .next()
+            .ok_or_else(|| AocError::unexpected_end(DAY, "number"))?;
+        let mut value = c.to_digit(10).ok_or_else(|| {
+            AocError::parse(DAY, format!("expected number, not '{}'", c)).at_column(start + 1)
+        })? as u8;
+
+        while let Some((_, c)) = s.peek() {
             if let Some(digit) = c.to_digit(10) {
                 s.next().unwrap();
-                value *= 10;
-                value += digit as u8;
+                value = value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(digit as u8))
+                    .ok_or_else(|| {
+                        AocError::parse(DAY, "packet value is too large").at_column(start + 1)
+                    })?;
             } else {
                 break;
             }
         }
 
-        PacketValue(value)
+        Ok(PacketValue(value))
     }
 
     fn is_in_order(left: &PacketValue, right: &PacketValue) -> Option<bool> {
@@ -57,27 +86,37 @@ impl PacketValue {
 }
 
 #[derive(Debug, PartialOrd, PartialEq, Clone)]
-struct PacketList(Vec<Packet>);
+pub struct PacketList(Vec<Packet>);
 
 impl PacketList {
-    fn parse(s: &mut Peekable<Chars>) -> PacketList {
-        assert_eq!(s.next(), Some('['));
+    fn parse(s: &mut Peekable<CharIndices>) -> Result<PacketList, AocError> {
+        match s.next() {
+            Some((_, '[')) => {}
+            Some((i, c)) => {
+                return Err(
+                    AocError::parse(DAY, format!("expected '[', not '{}'", c)).at_column(i + 1)
+                )
+            }
+            None => return Err(AocError::unexpected_end(DAY, "'['")),
+        }
 
         let mut list = Vec::new();
 
         loop {
-            let &char = s.peek().expect("unexpected end of packet list");
+            let &(_, char) = s
+                .peek()
+                .ok_or_else(|| AocError::unexpected_end(DAY, "end of packet list"))?;
             if char == '[' {
-                list.push(Packet::List(PacketList::parse(s)));
-                if s.peek() == Some(&',') {
+                list.push(Packet::List(PacketList::parse(s)?));
+                if matches!(s.peek(), Some((_, ','))) {
                     s.next();
                 }
             } else if char == ']' {
                 s.next();
-                return PacketList(list);
+                return Ok(PacketList(list));
             } else {
-                list.push(Packet::Value(PacketValue::parse(s)));
-                if s.peek() == Some(&',') {
+                list.push(Packet::Value(PacketValue::parse(s)?));
+                if matches!(s.peek(), Some((_, ','))) {
                     s.next();
                 }
             }
@@ -106,31 +145,35 @@ impl PacketList {
 }
 
 #[aoc_generator(day13)]
-fn packet_parser(input: &str) -> Vec<Packet> {
+pub fn packet_parser(input: &str) -> Result<Vec<Packet>, AocError> {
     let mut packets = Vec::new();
-    let mut input = input.chars().peekable();
-
-    loop {
-        let a = PacketList::parse(&mut input);
-        assert_eq!(input.next(), Some('\n'));
-        packets.push(Packet::List(a));
-
-        let b = PacketList::parse(&mut input);
-        packets.push(Packet::List(b));
-
-        if input.peek().is_none() {
-            break;
+    let mut num_lines = 0;
+
+    // packets come in pairs, with an empty line between each pair
+    for (i, line) in input.lines().enumerate() {
+        num_lines = i + 1;
+        if i % 3 == 2 {
+            if !line.is_empty() {
+                return Err(AocError::parse(
+                    DAY,
+                    format!("expected empty line between packet pairs, not '{}'", line),
+                )
+                .at_line(i + 1));
+            }
         } else {
-            assert_eq!(input.next(), Some('\n'));
-            assert_eq!(input.next(), Some('\n'));
+            packets.push(Packet::parse_line(line).map_err(|e| e.at_line(i + 1))?);
         }
     }
 
-    packets
+    if packets.len() % 2 != 0 {
+        return Err(AocError::unexpected_end(DAY, "second packet of pair").at_line(num_lines + 1));
+    }
+
+    Ok(packets)
 }
 
 #[aoc(day13, part1)]
-fn sum_valid_packet_pair_indexes(packets: &[Packet]) -> usize {
+pub fn sum_valid_packet_pair_indexes(packets: &[Packet]) -> usize {
     packets
         .iter()
         .tuples::<(_, _)>()
@@ -143,9 +186,9 @@ fn sum_valid_packet_pair_indexes(packets: &[Packet]) -> usize {
 }
 
 #[aoc(day13, part2)]
-fn decoder_key(packets: &[Packet]) -> usize {
-    let div_one = Packet::List(PacketList::parse(&mut "[[2]]".chars().peekable()));
-    let div_two = Packet::List(PacketList::parse(&mut "[[6]]".chars().peekable()));
+pub fn decoder_key(packets: &[Packet]) -> usize {
+    let div_one = Packet::parse_line("[[2]]").unwrap();
+    let div_two = Packet::parse_line("[[6]]").unwrap();
 
     let mut index_one = 1;
     let mut index_two = 2;
@@ -170,6 +213,29 @@ fn decoder_key(packets: &[Packet]) -> usize {
     index_one * index_two
 }
 
+/// Day 13: Distress Signal
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Packet>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        packet_parser(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(sum_valid_packet_pair_indexes(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(decoder_key(input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,15 +267,24 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let packet_pairs = packet_parser(INPUT);
+        let packet_pairs = packet_parser(INPUT).unwrap();
         let sum_valid_indexes = sum_valid_packet_pair_indexes(&packet_pairs);
         assert_eq!(sum_valid_indexes, 13)
     }
 
     #[test]
     fn test_part_two() {
-        let packet_pairs = packet_parser(INPUT);
+        let packet_pairs = packet_parser(INPUT).unwrap();
         let key = decoder_key(&packet_pairs);
         assert_eq!(key, 140)
     }
+
+    #[test]
+    fn test_parse_error() {
+        let error = packet_parser("[1,1,3,1,1]\n[1,[1;5],1,1]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 13, line 2, column 6: expected number, not ';'"
+        );
+    }
 }
@@ -1,11 +1,14 @@
+use crate::error::{parse_lines, parse_number, AocError};
+use crate::grid::{Grid, Position};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::cmp::{max, min};
 use std::fmt::{Display, Formatter};
-use std::num::ParseIntError;
-use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
+const DAY: u8 = 14;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Point {
     x: usize,
@@ -13,29 +16,48 @@ struct Point {
 }
 
 impl FromStr for Point {
-    type Err = ParseIntError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').expect("unexpected point format");
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| AocError::parse(DAY, format!("unexpected point format '{}'", s)))?;
         Ok(Point {
-            x: x.parse()?,
-            y: y.parse()?,
+            x: parse_number(DAY, x, "x")?,
+            y: parse_number(DAY, y, "y")?,
         })
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Path(Vec<Point>);
+pub struct Path(Vec<Point>);
 
 impl FromStr for Path {
-    type Err = ParseIntError;
+    type Err = AocError;
 
+    /// fails on a segment that isn't horizontal or vertical, at the column of its end
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Path(
-            s.split(" -> ")
-                .map(FromStr::from_str)
-                .collect::<Result<_, _>>()?,
-        ))
+        let mut column = 1;
+        let mut points: Vec<Point> = Vec::new();
+        for point in s.split(" -> ") {
+            let parsed: Point = point.parse().map_err(|e: AocError| e.at_column(column))?;
+            if let Some(previous) = points.last() {
+                if previous.x != parsed.x && previous.y != parsed.y {
+                    return Err(AocError::parse(
+                        DAY,
+                        format!(
+                            "diagonal segment from {},{} to {}",
+                            previous.x, previous.y, point
+                        ),
+                    )
+                    .at_column(column));
+                }
+            }
+            points.push(parsed);
+            column += point.len() + " -> ".len();
+        }
+
+        Ok(Path(points))
     }
 }
 
@@ -48,18 +70,15 @@ enum Tile {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct Scan {
-    raw: Vec<Tile>,
-    width: usize,
-    height: usize,
-    source: Point,
+    tiles: Grid<Tile>,
+    source: Position,
 }
 
 impl Display for Scan {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let point = Point { x, y };
-                let char = match self[&point] {
+        for row in self.tiles.rows() {
+            for tile in row {
+                let char = match tile {
                     Tile::Air => ".",
                     Tile::Rock => "#",
                     Tile::Sand => "+",
@@ -75,12 +94,13 @@ impl Display for Scan {
 impl Scan {
     const SOURCE: Point = Point { x: 500, y: 0 };
 
-    fn from_paths(paths: &[Path]) -> Scan {
+    /// a scan just big enough to hold `paths` and the sand's `source`
+    fn from_paths(paths: &[Path], source: Point) -> Scan {
         // clip left area
-        let mut left = Scan::SOURCE.x;
-        let mut right = Scan::SOURCE.x;
+        let mut left = source.x;
+        let mut right = source.x;
         const _TOP: usize = 0; // SAND_SOURCE is at 0
-        let mut bottom = Scan::SOURCE.y;
+        let mut bottom = source.y;
 
         for point in paths.iter().flat_map(|path| &path.0) {
             left = min(left, point.x);
@@ -89,41 +109,22 @@ impl Scan {
         }
 
         let adj_x = left;
-        let source = Point {
-            x: Scan::SOURCE.x - adj_x,
-            y: Scan::SOURCE.y,
-        };
+        let position = |point: &Point| (point.y, point.x - adj_x);
 
         // build scan
         let width = right - left + 1;
         let height = bottom + 1;
 
-        let raw = vec![Tile::Air; width * height];
         let mut scan = Scan {
-            raw,
-            width,
-            height,
-            source,
+            tiles: Grid::new(width, height, Tile::Air),
+            source: position(&source),
         };
 
         for path in paths {
             let first = path.0.first().expect("cannot have empty path");
-            let first = &Point {
-                x: first.x - adj_x,
-                y: first.y,
-            };
-            scan[first] = Tile::Rock;
+            scan.tiles[position(first)] = Tile::Rock;
 
             for (start, end) in path.0.iter().tuple_windows() {
-                let start = Point {
-                    x: start.x - adj_x,
-                    y: start.y,
-                };
-                let end = Point {
-                    x: end.x - adj_x,
-                    y: end.y,
-                };
-
                 let mut current = start.to_owned();
                 loop {
                     if current.x == end.x {
@@ -143,10 +144,10 @@ impl Scan {
                             break;
                         }
                     } else {
-                        panic!("path must consist of straight lines")
+                        unreachable!("paths are checked to be straight when parsed")
                     }
 
-                    scan[&current] = Tile::Rock;
+                    scan.tiles[position(&current)] = Tile::Rock;
                 }
             }
         }
@@ -155,120 +156,118 @@ impl Scan {
     }
 
     fn try_place_sand(&mut self) -> bool {
-        if self[&self.source] != Tile::Air {
+        if self.tiles[self.source] != Tile::Air {
             // source is blocked
             return false;
         }
 
-        let mut sand = self.source.to_owned();
-        loop {
-            let in_abyss_below = sand.y >= self.height - 1;
-            let in_abyss_left = sand.x == 0;
-            let in_abyss_right = sand.x >= self.width - 1;
-            if in_abyss_below || in_abyss_left || in_abyss_right {
-                return false;
-            }
-
-            let below = Point {
-                x: sand.x,
-                y: sand.y + 1,
-            };
-            let below_left = Point {
-                x: sand.x - 1,
-                y: sand.y + 1,
-            };
-            let below_right = Point {
-                x: sand.x + 1,
-                y: sand.y + 1,
-            };
-
-            if self[&below] == Tile::Air {
-                sand = below;
-            } else if self[&below_left] == Tile::Air {
-                sand = below_left;
-            } else if self[&below_right] == Tile::Air {
-                sand = below_right;
-            } else {
-                self[&sand] = Tile::Sand;
-                return true;
+        // below, below left, below right
+        const FALLS: [(isize, isize); 3] = [(1, 0), (1, -1), (1, 1)];
+
+        let mut sand = self.source;
+        'falling: loop {
+            for fall in FALLS {
+                match self.tiles.offset(sand, fall) {
+                    // falls off the edge of the scan, into the abyss
+                    None => return false,
+                    Some(next) if self.tiles[next] == Tile::Air => {
+                        sand = next;
+                        continue 'falling;
+                    }
+                    Some(_) => {}
+                }
             }
-        }
-    }
-}
-
-impl Index<&Point> for Scan {
-    type Output = Tile;
 
-    fn index(&self, index: &Point) -> &Self::Output {
-        if self.width <= index.x || self.height <= index.y {
-            panic!("index {:?} is out of range", index)
+            self.tiles[sand] = Tile::Sand;
+            return true;
         }
-
-        let index = index.x + index.y * self.width;
-        &self.raw[index]
-    }
-}
-
-impl IndexMut<&Point> for Scan {
-    fn index_mut(&mut self, index: &Point) -> &mut Self::Output {
-        let index = index.x + index.y * self.width;
-        &mut self.raw[index]
     }
 }
 
 #[aoc_generator(day14)]
-fn parse_paths(input: &str) -> Vec<Path> {
-    input
-        .lines()
-        .map(|line| line.parse())
-        .collect::<Result<_, _>>()
-        .expect("could not parse path")
+pub fn parse_paths(input: &str) -> Result<Vec<Path>, AocError> {
+    parse_lines(input, str::parse)
 }
 
 #[aoc(day14, part1)]
-fn amount_sand_rests(paths: &[Path]) -> usize {
-    let mut scan = Scan::from_paths(&paths);
-    // println!("{}", scan);
+pub fn amount_sand_rests(paths: &[Path]) -> usize {
+    amount_sand_rests_from(paths, Scan::SOURCE)
+}
+
+fn amount_sand_rests_from(paths: &[Path], source: Point) -> usize {
+    let mut scan = Scan::from_paths(paths, source);
 
     let mut count = 0;
 
     while scan.try_place_sand() {
-        // println!("{}", scan);
         count += 1;
     }
 
-    // println!("{}", scan);
     count
 }
 
 #[aoc(day14, part2)]
-fn amount_sand_rests_until_blocked(input: &[Path]) -> usize {
+pub fn amount_sand_rests_until_blocked(input: &[Path]) -> usize {
     // simulate "infinite" floor with a real path
-    let mut paths = Vec::with_capacity(input.len() + 1);
-    paths.extend(input.to_owned());
-
-    let bottom = paths
+    let bottom = input
         .iter()
         .flat_map(|path| &path.0)
         .map(|point| point.y)
         .max()
-        .expect("must have at least one path")
+        .unwrap_or(0)
         + 2;
-    let bottom_path = Path(vec![
+
+    // sand piles up no wider than the floor is deep, so shift everything right if that would
+    // take it past x = 0
+    let shift = bottom.saturating_sub(Scan::SOURCE.x);
+    let shifted = |point: &Point| Point {
+        x: point.x + shift,
+        y: point.y,
+    };
+    let source = shifted(&Scan::SOURCE);
+
+    let mut paths = Vec::with_capacity(input.len() + 1);
+    paths.extend(
+        input
+            .iter()
+            .map(|path| Path(path.0.iter().map(shifted).collect())),
+    );
+    paths.push(Path(vec![
         Point {
-            x: Scan::SOURCE.x - bottom,
+            x: source.x - bottom,
             y: bottom,
         },
         Point {
-            x: Scan::SOURCE.x + bottom,
+            x: source.x + bottom,
             y: bottom,
         },
-    ]);
-
-    paths.push(bottom_path);
+    ]));
 
     // then run as normal
-    amount_sand_rests(&paths)
+    amount_sand_rests_from(&paths, source)
+}
+
+/// Day 14: Regolith Reservoir
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Path>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_paths(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(amount_sand_rests(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(amount_sand_rests_until_blocked(input))
+    }
 }
 
 #[cfg(test)]
@@ -281,13 +280,31 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let paths = parse_paths(INPUT);
+        let paths = parse_paths(INPUT).unwrap();
         assert_eq!(amount_sand_rests(&paths), 24);
     }
 
     #[test]
     fn test_part_two() {
-        let paths = parse_paths(INPUT);
+        let paths = parse_paths(INPUT).unwrap();
         assert_eq!(amount_sand_rests_until_blocked(&paths), 93);
     }
+
+    #[test]
+    fn test_diagonal_path() {
+        let input = INPUT.replacen("502,4 -> 502,9", "502,4 -> 501,9", 1);
+        assert_eq!(
+            parse_paths(&input).unwrap_err().to_string(),
+            "day 14, line 2, column 19: diagonal segment from 502,4 to 501,9"
+        );
+    }
+
+    #[test]
+    fn test_floor_deeper_than_source_is_wide() {
+        // a rock 599 deep puts the floor further down than x = 0 is from the source, but a wide
+        // shelf stops the sand two rows down
+        let paths = parse_paths("0,599\n400,2 -> 600,2").unwrap();
+        assert_eq!(amount_sand_rests_until_blocked(&paths), 4);
+        assert_eq!(amount_sand_rests_until_blocked(&[]), 4);
+    }
 }
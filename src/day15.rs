@@ -1,47 +1,56 @@
+use crate::error::{parse_lines, parse_number, AocError};
+use crate::grid::{Grid, Position};
+use crate::ppm::{lerp, Rgb};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use std::cmp::{max, Ordering};
-use std::num::ParseIntError;
+use std::cmp::{max, min, Ordering};
 use std::ops::Range;
 use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Point {
-    x: i64,
-    y: i64,
+const DAY: u8 = 15;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
 }
 
 impl FromStr for Point {
-    type Err = String;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s
             .strip_prefix("x=")
-            .ok_or_else(|| format!("missing 'x=' in '{}'", s))?;
+            .ok_or_else(|| AocError::parse(DAY, format!("missing 'x=' in '{}'", s)))?;
         let (x, s) = s
             .split_once(',')
-            .ok_or_else(|| format!("missing ',' in '{}'", s))?;
-        let x = x.parse().map_err(|e: ParseIntError| e.to_string())?;
+            .ok_or_else(|| AocError::parse(DAY, format!("missing ',' in '{}'", s)))?;
+        let x = parse_number(DAY, x, "x")?;
 
         let y = s
             .strip_prefix(" y=")
-            .ok_or_else(|| format!("missing ' y=' in '{}'", s))?;
-        let y = y.parse().map_err(|e: ParseIntError| e.to_string())?;
+            .ok_or_else(|| AocError::parse(DAY, format!("missing ' y=' in '{}'", s)))?;
+        let y = parse_number(DAY, y, "y")?;
 
         Ok(Point { x, y })
     }
 }
 
 impl Point {
-    fn manhattan_distance(&self, other: &Point) -> u64 {
+    pub fn manhattan_distance(&self, other: &Point) -> u64 {
         self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
     }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Sensor(Point);
+pub struct Sensor(Point);
 
 impl Sensor {
+    pub fn position(&self) -> Point {
+        self.0
+    }
+
     fn x_range_for_y_in_manhattan_range(
         &self,
         manhattan_distance: u64,
@@ -80,89 +89,372 @@ fn collapse_ordered_range<T: PartialOrd + Ord>(
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Beacon(Point);
+pub struct Beacon(Point);
 
-#[aoc_generator(day15)]
-fn parse_sensor_report(input: &str) -> Vec<(Sensor, Beacon)> {
-    input
-        .lines()
-        .map(|line| {
-            let line = line.strip_prefix("Sensor at ").expect("unexpected format");
-            let (sensor, beacon) = line
-                .split_once(": closest beacon is at ")
-                .expect("unexpected format");
-            let sensor = Sensor(sensor.parse().expect("could not parse sensor location"));
-            let beacon = Beacon(beacon.parse().expect("could not parse beacon location"));
-            (sensor, beacon)
-        })
-        .collect()
+impl Beacon {
+    pub fn position(&self) -> Point {
+        self.0
+    }
 }
 
-fn known_empty_positions<const Y: i64>(input: &[(Sensor, Beacon)]) -> i64 {
-    let num_in_range: i64 = input
-        .iter()
-        .map(|(sensor, beacon)| {
-            let md = sensor.0.manhattan_distance(&beacon.0);
-            let range = sensor.x_range_for_y_in_manhattan_range(md, Y);
-            range
-        })
-        .filter_map(|range| range)
-        .sorted_unstable_by(order_range)
-        .coalesce(collapse_ordered_range)
-        .map(|range| range.end - range.start)
-        .sum();
+/// the positions covered by a report's sensors. a sensor covers every position no further from it
+/// than its closest beacon.
+#[derive(Debug, Clone)]
+pub struct SensorField {
+    /// each sensor with the distance it covers
+    sensors: Vec<(Sensor, u64)>,
+    /// deduplicated, since many sensors can share a closest beacon
+    beacons: Vec<Beacon>,
+}
 
-    let num_beacons = input
-        .iter()
-        .filter_map(|(_, beacon)| {
-            if beacon.0.y == Y {
-                Some(beacon.0.x)
-            } else {
-                None
+impl SensorField {
+    pub fn new(report: &[(Sensor, Beacon)]) -> Self {
+        let sensors = report
+            .iter()
+            .map(|(sensor, beacon)| (*sensor, sensor.0.manhattan_distance(&beacon.0)))
+            .collect();
+        let beacons = report
+            .iter()
+            .map(|(_, beacon)| *beacon)
+            .unique_by(|beacon| beacon.0)
+            .collect();
+        SensorField { sensors, beacons }
+    }
+
+    pub fn sensors(&self) -> impl Iterator<Item = Sensor> + '_ {
+        self.sensors.iter().map(|(sensor, _)| *sensor)
+    }
+
+    pub fn beacons(&self) -> impl Iterator<Item = Beacon> + '_ {
+        self.beacons.iter().copied()
+    }
+
+    /// the covered x ranges of row `y`, sorted and non-overlapping
+    pub fn covered_in_row(&self, y: i64) -> Vec<Range<i64>> {
+        self.coalesced_row(y).collect()
+    }
+
+    fn coalesced_row(&self, y: i64) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.sensors
+            .iter()
+            .filter_map(move |(sensor, distance)| {
+                sensor.x_range_for_y_in_manhattan_range(*distance, y)
+            })
+            .sorted_unstable_by(order_range)
+            .coalesce(collapse_ordered_range)
+    }
+
+    /// how many positions in row `y` are covered but can't hold a beacon
+    pub fn known_empty_in_row(&self, y: i64) -> i64 {
+        let covered: i64 = self
+            .coalesced_row(y)
+            .map(|range| range.end - range.start)
+            .sum();
+        // every beacon is covered by the sensor it's closest to
+        let beacons = self.beacons.iter().filter(|beacon| beacon.0.y == y).count() as i64;
+        covered - beacons
+    }
+
+    /// the uncovered x ranges of row `y` within `x_range`, sorted and non-overlapping
+    pub fn uncovered_in_row(&self, y: i64, x_range: Range<i64>) -> Vec<Range<i64>> {
+        let mut uncovered = Vec::new();
+        let mut x = x_range.start;
+        for covered in self.coalesced_row(y) {
+            if x >= x_range.end {
+                break;
+            }
+            if covered.start > x {
+                uncovered.push(x..min(covered.start, x_range.end));
             }
+            x = max(x, covered.end);
+        }
+        if x < x_range.end {
+            uncovered.push(x..x_range.end);
+        }
+        uncovered
+    }
+
+    /// every uncovered position within `x_range` and `y_range`, row by row
+    pub fn uncovered_in_rect(
+        &self,
+        x_range: Range<i64>,
+        y_range: Range<i64>,
+    ) -> impl Iterator<Item = Point> + '_ {
+        y_range.flat_map(move |y| {
+            self.uncovered_in_row(y, x_range.clone())
+                .into_iter()
+                .flat_map(move |xs| xs.map(move |x| Point { x, y }))
         })
-        .unique()
-        .count() as i64;
-    num_in_range - num_beacons
+    }
+
+    pub fn is_covered(&self, point: Point) -> bool {
+        self.covering_sensors(point).next().is_some()
+    }
+
+    pub fn covering_sensors(&self, point: Point) -> impl Iterator<Item = Sensor> + '_ {
+        self.sensors
+            .iter()
+            .filter(move |(sensor, distance)| sensor.0.manhattan_distance(&point) <= *distance)
+            .map(|(sensor, _)| *sensor)
+    }
+}
+
+const UNCOVERED: Rgb = [0, 0, 0];
+const LEAST_COVERED: Rgb = [30, 40, 110];
+const MOST_COVERED: Rgb = [250, 220, 90];
+const SENSOR: Rgb = [230, 40, 40];
+const BEACON: Rgb = [40, 210, 80];
+const DISTRESS: Rgb = [255, 255, 255];
+
+impl SensorField {
+    /// draws the window `x_range` by `y_range` of the plane as a `width` by `height` image,
+    /// sampling one position per pixel. positions are coloured by how many sensors cover them
+    /// (relative to the most covered position in the window), and sensors, beacons and `distress`
+    /// are marked with squares big enough to see when zoomed out.
+    pub fn render(
+        &self,
+        x_range: Range<i64>,
+        y_range: Range<i64>,
+        width: usize,
+        height: usize,
+        distress: Option<Point>,
+    ) -> Grid<Rgb> {
+        assert!(!x_range.is_empty() && !y_range.is_empty(), "empty window");
+        let span = |range: &Range<i64>| (range.end - range.start) as i128;
+        let (x_span, y_span) = (span(&x_range), span(&y_range));
+
+        let to_plane = |(row, col): Position| Point {
+            x: x_range.start + (col as i128 * x_span / width as i128) as i64,
+            y: y_range.start + (row as i128 * y_span / height as i128) as i64,
+        };
+        let to_pixel = |point: Point| -> Option<Position> {
+            (x_range.contains(&point.x) && y_range.contains(&point.y)).then(|| {
+                let row = (point.y - y_range.start) as i128 * height as i128 / y_span;
+                let col = (point.x - x_range.start) as i128 * width as i128 / x_span;
+                (row as usize, col as usize)
+            })
+        };
+
+        let mut coverage = Grid::new(width, height, 0);
+        for position in coverage.positions() {
+            coverage[position] = self.covering_sensors(to_plane(position)).count();
+        }
+        let most_covered = coverage.iter().copied().max().unwrap_or(0);
+        let mut image = coverage.map(|&covering| match covering {
+            0 => UNCOVERED,
+            _ => {
+                let t = (covering - 1) as f64 / max(most_covered - 1, 1) as f64;
+                lerp(LEAST_COVERED, MOST_COVERED, t)
+            }
+        });
+
+        let radius = max(min(width, height) / 200, 1) as isize;
+        let marks = self
+            .sensors()
+            .map(|sensor| (sensor.0, SENSOR))
+            .chain(self.beacons().map(|beacon| (beacon.0, BEACON)))
+            .chain(distress.map(|point| (point, DISTRESS)));
+        for (point, colour) in marks {
+            let Some(centre) = to_pixel(point) else {
+                continue;
+            };
+            for offset in (-radius..=radius).cartesian_product(-radius..=radius) {
+                if let Some(position) = image.offset(centre, offset) {
+                    image[position] = colour;
+                }
+            }
+        }
+
+        image
+    }
+}
+
+#[aoc_generator(day15)]
+pub fn parse_sensor_report(input: &str) -> Result<Vec<(Sensor, Beacon)>, AocError> {
+    parse_lines(input, |line| {
+        let full_line = line;
+        let line = line
+            .strip_prefix("Sensor at ")
+            .ok_or_else(|| AocError::parse(DAY, "expected 'Sensor at '").at_column(1))?;
+        let (sensor, beacon) = line
+            .split_once(": closest beacon is at ")
+            .ok_or_else(|| AocError::parse(DAY, "expected ': closest beacon is at '"))?;
+        let column = |s: &str| full_line.len() - s.len() + 1;
+
+        let sensor = Sensor(
+            sensor
+                .parse()
+                .map_err(|e: AocError| e.at_column(column(line)))?,
+        );
+        let beacon = Beacon(
+            beacon
+                .parse()
+                .map_err(|e: AocError| e.at_column(column(beacon)))?,
+        );
+        Ok((sensor, beacon))
+    })
+}
+
+fn known_empty_positions<const Y: i64>(input: &[(Sensor, Beacon)]) -> i64 {
+    SensorField::new(input).known_empty_in_row(Y)
 }
 
 #[aoc(day15, part1)]
-fn known_empty_positions_2_000_000(input: &[(Sensor, Beacon)]) -> i64 {
+pub fn known_empty_positions_2_000_000(input: &[(Sensor, Beacon)]) -> i64 {
     const Y: i64 = 2_000_000;
     known_empty_positions::<Y>(input)
 }
 
-fn find_distress_signal<const SEARCH_SPACE: i64>(input: &[(Sensor, Beacon)]) -> Option<Point> {
-    for y in 0..=SEARCH_SPACE {
-        let mut collapsed_range = input
-            .iter()
-            .map(|(sensor, beacon)| {
-                let md = sensor.0.manhattan_distance(&beacon.0);
-                let range = sensor.x_range_for_y_in_manhattan_range(md, y);
-                range
+/// the first uncovered position in row `y` with `0 <= x <= search_space`, if any
+fn find_distress_signal_in_row(field: &SensorField, search_space: i64, y: i64) -> Option<Point> {
+    let x = field
+        .uncovered_in_row(y, 0..search_space + 1)
+        .first()?
+        .start;
+    Some(Point { x, y })
+}
+
+#[cfg(not(feature = "parallel"))]
+fn scan_rows_for_distress_signal(field: &SensorField, search_space: i64) -> Option<Point> {
+    (0..=search_space).find_map(|y| find_distress_signal_in_row(field, search_space, y))
+}
+
+/// searches rows in parallel, still returning the lowest row with a gap so the result matches the
+/// sequential search. rows after a found gap are skipped.
+#[cfg(feature = "parallel")]
+fn scan_rows_for_distress_signal(field: &SensorField, search_space: i64) -> Option<Point> {
+    use rayon::prelude::*;
+
+    (0..=search_space)
+        .into_par_iter()
+        .find_map_first(|y| find_distress_signal_in_row(field, search_space, y))
+}
+
+/// checks only the points where the lines just outside each sensor's range cross each other or the
+/// edges of the search space, since the first uncovered point (by row, then column) is hemmed in
+/// by the sensors covering the points above and to the left of it.
+///
+/// lines are stored as `y - x` (ascending) and `y + x` (descending).
+fn check_perimeters_for_distress_signal(field: &SensorField, search_space: i64) -> Option<Point> {
+    let mut ascending = Vec::with_capacity(2 * field.sensors.len());
+    let mut descending = Vec::with_capacity(2 * field.sensors.len());
+    for (Sensor(sensor), distance) in &field.sensors {
+        let outside = *distance as i64 + 1;
+        ascending.extend([sensor.y - sensor.x - outside, sensor.y - sensor.x + outside]);
+        descending.extend([sensor.y + sensor.x - outside, sensor.y + sensor.x + outside]);
+    }
+    ascending.sort_unstable();
+    ascending.dedup();
+    descending.sort_unstable();
+    descending.dedup();
+
+    let size = search_space;
+    // lines of opposite parity cross between cells, and a gap ending there leaves the integer
+    // points around the crossing uncovered instead, so check all four of those
+    let crossings = ascending
+        .iter()
+        .cartesian_product(&descending)
+        .flat_map(|(a, d)| {
+            let (x, y) = ((d - a).div_euclid(2), (a + d).div_euclid(2));
+            let around = if (a + d) % 2 == 0 {
+                &[(0, 0)][..]
+            } else {
+                &[(0, 0), (1, 0), (0, 1), (1, 1)][..]
+            };
+            around.iter().map(move |(dx, dy)| Point {
+                x: x + dx,
+                y: y + dy,
             })
-            .filter_map(|range| range)
-            .sorted_unstable_by(order_range)
-            .coalesce(collapse_ordered_range)
-            .skip_while(|range| range.end <= 0);
+        });
+    let on_edges = ascending
+        .iter()
+        .flat_map(|&a| [(0, a), (size, a + size), (-a, 0), (size - a, size)])
+        .chain(
+            descending
+                .iter()
+                .flat_map(|&d| [(0, d), (size, d - size), (d, 0), (d - size, size)]),
+        )
+        .chain([(0, 0), (size, 0), (0, size), (size, size)])
+        .map(|(x, y)| Point { x, y });
 
-        let first_range = collapsed_range.next().expect("no range in row");
-        assert!(first_range.start <= 0);
-        if first_range.end <= SEARCH_SPACE {
-            let x = first_range.end;
-            return Some(Point { x, y });
-        }
+    crossings
+        .chain(on_edges)
+        .filter(|point| (0..=size).contains(&point.x) && (0..=size).contains(&point.y))
+        .filter(|point| !field.is_covered(*point))
+        .min_by_key(|point| (point.y, point.x))
+}
+
+/// how [`find_distress_signal`] searches
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum SearchMode {
+    /// coalesces the sensors' coverage of every row in turn
+    #[default]
+    RowScan,
+    /// checks only the candidate points on the edges of the sensors' ranges. this is independent
+    /// of the size of the search space.
+    Perimeter,
+}
+
+/// finds the uncovered position with `0 <= x, y <= search_space` in the lowest row (and then
+/// column)
+pub fn find_distress_signal(
+    input: &[(Sensor, Beacon)],
+    search_space: i64,
+    mode: SearchMode,
+) -> Option<Point> {
+    let field = SensorField::new(input);
+    match mode {
+        SearchMode::RowScan => scan_rows_for_distress_signal(&field, search_space),
+        SearchMode::Perimeter => check_perimeters_for_distress_signal(&field, search_space),
     }
+}
+
+fn tuning_frequency_with_mode(
+    input: &[(Sensor, Beacon)],
+    mode: SearchMode,
+) -> Result<i64, AocError> {
+    const SIZE: i64 = 4_000_000;
 
-    None
+    let point = find_distress_signal(input, SIZE, mode).ok_or_else(|| {
+        AocError::solve(
+            DAY,
+            format!("no distress signal found with 0 <= x, y <= {}", SIZE),
+        )
+    })?;
+    Ok(point.x * SIZE + point.y)
 }
 
 #[aoc(day15, part2)]
-fn tuning_frequency(input: &[(Sensor, Beacon)]) -> i64 {
-    const SIZE: i64 = 4_000_000;
+pub fn tuning_frequency(input: &[(Sensor, Beacon)]) -> Result<i64, AocError> {
+    tuning_frequency_with_mode(input, SearchMode::RowScan)
+}
 
-    let point = find_distress_signal::<SIZE>(input).expect("no distress signal found");
-    point.x * SIZE + point.y
+#[aoc(day15, part2, Perimeter)]
+pub fn tuning_frequency_perimeter(input: &[(Sensor, Beacon)]) -> Result<i64, AocError> {
+    tuning_frequency_with_mode(input, SearchMode::Perimeter)
+}
+
+/// Day 15: Beacon Exclusion Zone
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<(Sensor, Beacon)>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_sensor_report(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(known_empty_positions_2_000_000(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        tuning_frequency(input)
+    }
 }
 
 #[cfg(test)]
@@ -187,15 +479,150 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn test_part_one() {
-        let input = parse_sensor_report(INPUT);
+        let input = parse_sensor_report(INPUT).unwrap();
         let count = known_empty_positions::<10>(&input);
         assert_eq!(count, 26)
     }
 
     #[test]
     fn test_part_two() {
-        let input = parse_sensor_report(INPUT);
-        let distress_signal = find_distress_signal::<20>(&input);
-        assert_eq!(distress_signal, Some(Point { x: 14, y: 11 }))
+        let input = parse_sensor_report(INPUT).unwrap();
+        for mode in [SearchMode::RowScan, SearchMode::Perimeter] {
+            let distress_signal = find_distress_signal(&input, 20, mode);
+            assert_eq!(distress_signal, Some(Point { x: 14, y: 11 }), "{:?}", mode)
+        }
+    }
+
+    #[test]
+    fn test_no_distress_signal() {
+        // one sensor whose range reaches every corner of the search space
+        let input = parse_sensor_report(
+            "Sensor at x=2000000, y=2000000: closest beacon is at x=6000000, y=2000000",
+        )
+        .unwrap();
+        assert_eq!(
+            tuning_frequency_perimeter(&input).unwrap_err().to_string(),
+            "day 15: no distress signal found with 0 <= x, y <= 4000000"
+        );
+    }
+
+    #[test]
+    fn test_find_distress_signal_takes_lowest_row() {
+        // a larger search space has gaps in many rows, so the first one found must be the lowest
+        let input = parse_sensor_report(INPUT).unwrap();
+        let field = SensorField::new(&input);
+        let sequential = (0..=30).find_map(|y| find_distress_signal_in_row(&field, 30, y));
+        assert!(sequential.is_some());
+        assert_eq!(
+            find_distress_signal(&input, 30, SearchMode::RowScan),
+            sequential
+        );
+    }
+
+    #[test]
+    fn test_perimeter_matches_row_scan() {
+        let field = SensorField::new(&parse_sensor_report(INPUT).unwrap());
+        for size in [0, 1, 5, 14, 20, 26, 30] {
+            assert_eq!(
+                check_perimeters_for_distress_signal(&field, size),
+                (0..=size).find_map(|y| find_distress_signal_in_row(&field, size, y)),
+                "search space {}",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn test_perimeter_with_several_gaps() {
+        let sensor = |sx, sy, bx, by| {
+            (
+                Sensor(Point { x: sx, y: sy }),
+                Beacon(Point { x: bx, y: by }),
+            )
+        };
+        // the lowest gap ends between cells, where the bounding lines cross at a half-integer
+        let report = [
+            sensor(1, 13, -2, 13),
+            sensor(12, 11, 16, 14),
+            sensor(3, 0, 0, 3),
+            sensor(16, 1, 19, -3),
+            sensor(16, 12, 20, 8),
+        ];
+        let field = SensorField::new(&report);
+        assert!(field.uncovered_in_rect(0..21, 0..21).count() > 1);
+        assert_eq!(
+            check_perimeters_for_distress_signal(&field, 20),
+            Some(Point { x: 8, y: 2 })
+        );
+
+        // pseudo-random sensor sets, which mostly leave several gaps
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random = |n: i64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n as u64) as i64
+        };
+        for _ in 0..2000 {
+            let report = (0..1 + random(6))
+                .map(|_| {
+                    let (x, y) = (random(29) - 4, random(29) - 4);
+                    sensor(x, y, x + random(13) - 6, y + random(13) - 6)
+                })
+                .collect_vec();
+            let field = SensorField::new(&report);
+            assert_eq!(
+                check_perimeters_for_distress_signal(&field, 20),
+                (0..=20).find_map(|y| find_distress_signal_in_row(&field, 20, y)),
+                "{:?}",
+                report
+            );
+        }
+    }
+
+    #[test]
+    fn test_sensor_field() {
+        let field = SensorField::new(&parse_sensor_report(INPUT).unwrap());
+        assert_eq!(field.sensors().count(), 14);
+        assert_eq!(field.beacons().count(), 6);
+
+        assert_eq!(field.covered_in_row(10), vec![-2..25]);
+        assert_eq!(field.known_empty_in_row(10), 26);
+        assert_eq!(field.covered_in_row(11), vec![-3..14, 15..26]);
+        assert_eq!(field.uncovered_in_row(11, 0..21), vec![14..15]);
+        assert_eq!(field.uncovered_in_row(11, -10..-5), vec![-10..-5]);
+        assert_eq!(
+            field.uncovered_in_rect(0..21, 0..21).collect_vec(),
+            vec![Point { x: 14, y: 11 }]
+        );
+
+        assert!(!field.is_covered(Point { x: 14, y: 11 }));
+        assert!(field.is_covered(Point { x: 13, y: 11 }));
+        let sensors = field
+            .covering_sensors(Point { x: 13, y: 11 })
+            .map(|sensor| sensor.position())
+            .collect_vec();
+        assert_eq!(sensors, vec![Point { x: 12, y: 14 }, Point { x: 8, y: 7 }]);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = parse_sensor_report(INPUT).unwrap();
+        let field = SensorField::new(&input);
+        let distress = find_distress_signal(&input, 20, SearchMode::Perimeter);
+        let image = field.render(0..21, 0..21, 21, 21, distress);
+
+        assert_eq!((image.width(), image.height()), (21, 21));
+        assert_eq!(image[(11, 14)], DISTRESS);
+        assert_eq!(image[(18, 2)], SENSOR);
+        assert_eq!(image[(16, 10)], BEACON);
+        assert_ne!(image[(10, 10)], UNCOVERED);
+
+        // halving the resolution samples every other position
+        let image = field.render(0..21, 0..21, 11, 11, None);
+        assert_eq!(image[(9, 1)], SENSOR);
+
+        let image = field.render(-100..-90, -100..-90, 10, 10, None);
+        assert!(image.iter().all(|&pixel| pixel == UNCOVERED));
     }
 }
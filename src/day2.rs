@@ -1,5 +1,9 @@
+use crate::error::{parse_lines, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 
+const DAY: u8 = 2;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 enum HandShape {
     Rock,
@@ -8,21 +12,27 @@ enum HandShape {
 }
 
 impl HandShape {
-    fn from_opponent_play(c: char) -> Result<HandShape, String> {
+    fn from_opponent_play(c: char) -> Result<HandShape, AocError> {
         match c {
             'A' => Ok(HandShape::Rock),
             'B' => Ok(HandShape::Paper),
             'C' => Ok(HandShape::Scissor),
-            _ => Err(format!("unrecognized opponent HandShape character '{c}'")),
+            _ => Err(AocError::parse(
+                DAY,
+                format!("unrecognized opponent HandShape character '{c}'"),
+            )),
         }
     }
 
-    fn from_our_play(c: char) -> Result<HandShape, String> {
+    fn from_our_play(c: char) -> Result<HandShape, AocError> {
         match c {
             'X' => Ok(HandShape::Rock),
             'Y' => Ok(HandShape::Paper),
             'Z' => Ok(HandShape::Scissor),
-            _ => Err(format!("unrecognized opponent HandShape character '{c}'")),
+            _ => Err(AocError::parse(
+                DAY,
+                format!("unrecognized our HandShape character '{c}'"),
+            )),
         }
     }
 
@@ -77,12 +87,15 @@ impl GameResult {
         }
     }
 
-    fn from_expected_result(c: char) -> Result<GameResult, String> {
+    fn from_expected_result(c: char) -> Result<GameResult, AocError> {
         match c {
             'X' => Ok(GameResult::Loss),
             'Y' => Ok(GameResult::Draw),
             'Z' => Ok(GameResult::Win),
-            _ => Err(format!("unrecognized game result character '{c}'")),
+            _ => Err(AocError::parse(
+                DAY,
+                format!("unrecognized game result character '{c}'"),
+            )),
         }
     }
 }
@@ -113,30 +126,41 @@ impl StrategyGameResult {
     }
 }
 
+/// splits a line into its two columns, e.g. "A Y" into ('A', 'Y')
+fn parse_columns(line: &str) -> Result<(char, char), AocError> {
+    let mut iter = line.chars();
+    let first = iter
+        .next()
+        .ok_or_else(|| AocError::unexpected_end(DAY, "hand shape for opponent").at_column(1))?;
+
+    match iter.next() {
+        Some(' ') => {}
+        Some(c) => {
+            return Err(AocError::parse(DAY, format!("expected space, not '{}'", c)).at_column(2))
+        }
+        None => return Err(AocError::unexpected_end(DAY, "space").at_column(2)),
+    }
+
+    let second = iter
+        .next()
+        .ok_or_else(|| AocError::unexpected_end(DAY, "second column").at_column(3))?;
+
+    if let Some(c) = iter.next() {
+        return Err(AocError::parse(DAY, format!("expected newline, not '{}'", c)).at_column(4));
+    }
+
+    Ok((first, second))
+}
+
 #[aoc_generator(day2, part1)]
-pub fn parse_strategies(input: &str) -> Vec<Strategy> {
-    input
-        .lines()
-        .map(|line| {
-            let mut iter = line.chars();
-            let opponent = iter.next().expect("no hand shape for opponent");
-            let opponent = HandShape::from_opponent_play(opponent).unwrap();
-
-            let space = iter.next();
-            if space != Some(' ') {
-                panic!("expected space, not '{:?}'", space);
-            }
-
-            let our = iter.next().expect("no hand shape for us");
-            let our = HandShape::from_our_play(our).unwrap();
-
-            if let Some(c) = iter.next() {
-                panic!("expected newline, not '{}'", c);
-            }
-
-            Strategy { opponent, our }
-        })
-        .collect()
+pub fn parse_strategies(input: &str) -> Result<Vec<Strategy>, AocError> {
+    parse_lines(input, |line| {
+        let (opponent, our) = parse_columns(line)?;
+        let opponent = HandShape::from_opponent_play(opponent).map_err(|e| e.at_column(1))?;
+        let our = HandShape::from_our_play(our).map_err(|e| e.at_column(3))?;
+
+        Ok(Strategy { opponent, our })
+    })
 }
 
 #[aoc(day2, part1)]
@@ -145,32 +169,18 @@ pub fn total_score_strategies(input: &[Strategy]) -> u32 {
 }
 
 #[aoc_generator(day2, part2)]
-pub fn parse_strategy_game_result(input: &str) -> Vec<StrategyGameResult> {
-    input
-        .lines()
-        .map(|line| {
-            let mut iter = line.chars();
-            let opponent = iter.next().expect("no hand shape for opponent");
-            let opponent = HandShape::from_opponent_play(opponent).unwrap();
-
-            let space = iter.next();
-            if space != Some(' ') {
-                panic!("expected space, not '{:?}'", space);
-            }
-
-            let game_result = iter.next().expect("no game result");
-            let game_result = GameResult::from_expected_result(game_result).unwrap();
-
-            if let Some(c) = iter.next() {
-                panic!("expected newline, not '{}'", c);
-            }
-
-            StrategyGameResult {
-                opponent,
-                game_result,
-            }
+pub fn parse_strategy_game_result(input: &str) -> Result<Vec<StrategyGameResult>, AocError> {
+    parse_lines(input, |line| {
+        let (opponent, game_result) = parse_columns(line)?;
+        let opponent = HandShape::from_opponent_play(opponent).map_err(|e| e.at_column(1))?;
+        let game_result =
+            GameResult::from_expected_result(game_result).map_err(|e| e.at_column(3))?;
+
+        Ok(StrategyGameResult {
+            opponent,
+            game_result,
         })
-        .collect()
+    })
 }
 
 #[aoc(day2, part2)]
@@ -178,6 +188,30 @@ pub fn total_score_strategy_game_results(input: &[StrategyGameResult]) -> u32 {
     input.iter().map(StrategyGameResult::score).sum()
 }
 
+/// Day 2: Rock Paper Scissors
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = DAY;
+
+    // the second column means something different in each part
+    type Input = (Vec<Strategy>, Vec<StrategyGameResult>);
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        Ok((parse_strategies(input)?, parse_strategy_game_result(input)?))
+    }
+
+    fn part1((strategies, _): &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(total_score_strategies(strategies))
+    }
+
+    fn part2((_, strategies): &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(total_score_strategy_game_results(strategies))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,7 +219,7 @@ mod tests {
     #[test]
     fn test_part_one() {
         let input = "A Y\nB X\nC Z";
-        let strategies = parse_strategies(input);
+        let strategies = parse_strategies(input).unwrap();
         let score = total_score_strategies(&strategies);
         assert_eq!(score, 15);
     }
@@ -193,8 +227,17 @@ mod tests {
     #[test]
     fn test_part_two() {
         let input = "A Y\nB X\nC Z";
-        let strategies = parse_strategy_game_result(input);
+        let strategies = parse_strategy_game_result(input).unwrap();
         let score = total_score_strategy_game_results(&strategies);
         assert_eq!(score, 12);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_strategy_game_result("A Y\nB W\nC Z").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 2, line 2, column 3: unrecognized game result character 'W'"
+        );
+    }
 }
@@ -1,59 +1,106 @@
+use crate::error::{parse_lines, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashSet;
 
+const DAY: u8 = 3;
+
 #[aoc_generator(day3)]
-pub fn build_rucksacks(input: &str) -> Vec<String> {
-    input.lines().map(|l| l.to_owned()).collect()
+pub fn build_rucksacks(input: &str) -> Result<Vec<String>, AocError> {
+    parse_lines(input, |line| {
+        for (i, item) in line.chars().enumerate() {
+            item_type(item).map_err(|e| e.at_column(i + 1))?;
+        }
+
+        Ok(line.to_owned())
+    })
 }
 
-fn duplicate_item(a: &str, b: &str) -> char {
+fn duplicate_item(a: &str, b: &str) -> Option<char> {
     let a: HashSet<_> = HashSet::from_iter(a.chars());
-    b.chars()
-        .find(|b| a.contains(b))
-        .expect("no incorrectly packed item")
+    b.chars().find(|b| a.contains(b))
 }
 
-fn badge(a: &str, b: &str, c: &str) -> char {
+fn badge(a: &str, b: &str, c: &str) -> Option<char> {
     let a: HashSet<_> = HashSet::from_iter(a.chars());
     let b: HashSet<_> = HashSet::from_iter(b.chars());
-    c.chars()
-        .find(|c| a.contains(c) && b.contains(c))
-        .expect("no common (badge) item")
+    c.chars().find(|c| a.contains(c) && b.contains(c))
 }
 
-fn item_type(item: char) -> Result<u32, String> {
+fn item_type(item: char) -> Result<u32, AocError> {
     match item {
         'a'..='z' => Ok(item as u32 - 'a' as u32 + 1),
         'A'..='Z' => Ok(item as u32 - 'A' as u32 + 27),
-        _ => Err(format!("invalid item type '{}'", item)),
+        _ => Err(AocError::parse(
+            DAY,
+            format!("invalid item type '{}'", item),
+        )),
     }
 }
 
 #[aoc(day3, part1)]
-pub fn duplicate_item_type_priority_sum(input: &[String]) -> u32 {
+pub fn duplicate_item_type_priority_sum(input: &[String]) -> Result<u32, AocError> {
     input
         .iter()
-        .map(|line| line.split_at(line.len() / 2))
-        .map(|(a, b)| duplicate_item(a, b))
-        .map(|item| item_type(item).unwrap())
+        .enumerate()
+        .map(|(i, line)| {
+            let (a, b) = line.split_at(line.len() / 2);
+            let item = duplicate_item(a, b).ok_or_else(|| {
+                AocError::solve(
+                    DAY,
+                    format!("rucksack {} has no incorrectly packed item", i + 1),
+                )
+            })?;
+            item_type(item)
+        })
         .sum()
 }
 
 #[aoc(day3, part2)]
-pub fn badge_item_type_priority_sum(input: &[String]) -> u32 {
-    input
-        .chunks(3)
-        .map(|chunk| {
-            if let [a, b, c] = chunk {
-                badge(a, b, c)
-            } else {
-                panic!("elves must be split into groups of three")
-            }
+pub fn badge_item_type_priority_sum(input: &[String]) -> Result<u32, AocError> {
+    let (groups, rest) = input.as_chunks::<3>();
+    if !rest.is_empty() {
+        return Err(AocError::solve(
+            DAY,
+            format!("{} elves can't be split into groups of three", input.len()),
+        ));
+    }
+
+    groups
+        .iter()
+        .enumerate()
+        .map(|(i, [a, b, c])| {
+            let item = badge(a, b, c).ok_or_else(|| {
+                AocError::solve(DAY, format!("group {} has no common (badge) item", i + 1))
+            })?;
+            item_type(item)
         })
-        .map(|item| item_type(item).unwrap())
         .sum()
 }
 
+/// Day 3: Rucksack Reorganization
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        build_rucksacks(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        duplicate_item_type_priority_sum(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        badge_item_type_priority_sum(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,15 +115,34 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
 
     #[test]
     fn test_part_one() {
-        let rucksacks = build_rucksacks(INPUT);
+        let rucksacks = build_rucksacks(INPUT).unwrap();
         let sum = duplicate_item_type_priority_sum(&rucksacks);
-        assert_eq!(sum, 157);
+        assert_eq!(sum, Ok(157));
     }
 
     #[test]
     fn test_part_two() {
-        let rucksacks = build_rucksacks(INPUT);
+        let rucksacks = build_rucksacks(INPUT).unwrap();
         let sum = badge_item_type_priority_sum(&rucksacks);
-        assert_eq!(sum, 70);
+        assert_eq!(sum, Ok(70));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let rucksacks = build_rucksacks("abcd\nabab\nbcbc\nefef").unwrap();
+        assert_eq!(
+            Day3::part1(&rucksacks).unwrap_err().to_string(),
+            "day 3: rucksack 1 has no incorrectly packed item"
+        );
+        assert_eq!(
+            Day3::part2(&rucksacks).unwrap_err().to_string(),
+            "day 3: 4 elves can't be split into groups of three"
+        );
+        assert_eq!(
+            badge_item_type_priority_sum(&rucksacks[1..])
+                .unwrap_err()
+                .to_string(),
+            "day 3: group 1 has no common (badge) item"
+        );
     }
 }
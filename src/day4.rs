@@ -1,29 +1,44 @@
+use crate::error::{parse_lines, parse_number, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::ops::Range;
 
+const DAY: u8 = 4;
+
+/// the section assignments of a pair of elves
+type AssignmentPair = (Range<u32>, Range<u32>);
+
+/// parses an inclusive "start-end" section assignment
+fn parse_range(s: &str, name: &str) -> Result<Range<u32>, AocError> {
+    let (start, end) = s.split_once('-').ok_or_else(|| {
+        AocError::parse(
+            DAY,
+            format!("could not find {name}_start and {name}_end in '{s}'"),
+        )
+    })?;
+    let start: u32 = parse_number(DAY, start, &format!("{name}_start"))?;
+    let end: u32 = parse_number(DAY, end, &format!("{name}_end"))?;
+
+    // RangeInclusive does not have public fields
+    Ok(start..end + 1)
+}
+
 #[aoc_generator(day4)]
-fn parse_ranges(input: &str) -> Vec<(Range<u32>, Range<u32>)> {
-    input
-        .lines()
-        .map(|l| {
-            let (a, b) = l.split_once(',').expect("could not find two ranges");
-
-            let (a_start, a_end) = a.split_once('-').expect("could not find a_start and a_end");
-            let a_start = a_start.parse().expect("unable to parse a_start");
-            let a_end: u32 = a_end.parse().expect("unable to parse a_end");
-
-            let (b_start, b_end) = b.split_once('-').expect("could not find b_start and b_end");
-            let b_start: u32 = b_start.parse().expect("unable to parse b_start");
-            let b_end: u32 = b_end.parse().expect("unable to parse b_end");
-
-            // RangeInclusive does not have public fields
-            (a_start..a_end + 1, b_start..b_end + 1)
-        })
-        .collect()
+pub fn parse_ranges(input: &str) -> Result<Vec<AssignmentPair>, AocError> {
+    parse_lines(input, |l| {
+        let (a, b) = l
+            .split_once(',')
+            .ok_or_else(|| AocError::parse(DAY, "could not find two ranges"))?;
+
+        let a = parse_range(a, "a").map_err(|e| e.at_column(1))?;
+        let b = parse_range(b, "b").map_err(|e| e.at_column(l.len() - b.len() + 1))?;
+
+        Ok((a, b))
+    })
 }
 
 #[aoc(day4, part1)]
-fn num_full_overlap(ranges: &[(Range<u32>, Range<u32>)]) -> usize {
+pub fn num_full_overlap(ranges: &[AssignmentPair]) -> usize {
     ranges
         .iter()
         .filter(|(a, b)| {
@@ -34,15 +49,36 @@ fn num_full_overlap(ranges: &[(Range<u32>, Range<u32>)]) -> usize {
 }
 
 #[aoc(day4, part2)]
-fn num_any_overlap(ranges: &[(Range<u32>, Range<u32>)]) -> usize {
+pub fn num_any_overlap(ranges: &[AssignmentPair]) -> usize {
     ranges
         .iter()
-        .filter(|(a, b)| {
-            (a.start < b.start && a.end > b.start) || (a.start < b.end && a.end > b.start)
-        })
+        .filter(|(a, b)| a.start < b.end && a.end > b.start)
         .count()
 }
 
+/// Day 4: Camp Cleanup
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<AssignmentPair>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_ranges(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(num_full_overlap(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(num_any_overlap(input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::day4::{num_any_overlap, num_full_overlap, parse_ranges};
@@ -57,14 +93,14 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let ranges = parse_ranges(INPUT);
+        let ranges = parse_ranges(INPUT).unwrap();
         let sum = num_full_overlap(&ranges);
         assert_eq!(sum, 2)
     }
 
     #[test]
     fn test_part_two() {
-        let ranges = parse_ranges(INPUT);
+        let ranges = parse_ranges(INPUT).unwrap();
         let sum = num_any_overlap(&ranges);
         assert_eq!(sum, 4)
     }
@@ -1,49 +1,68 @@
+use crate::error::{parse_number, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 
+const DAY: u8 = 5;
+
 #[derive(Debug, Clone)]
 pub struct Stacks(Vec<Vec<char>>);
 
 impl Stacks {
-    fn parse(input: &str) -> Stacks {
+    fn parse(input: &str) -> Result<Stacks, AocError> {
         let lines: Vec<_> = input.lines().collect();
 
-        let (_, count) = lines
+        let number_line = lines
             .last()
-            .expect("stack does not contain number line")
-            .trim_end()
-            .rsplit_once(' ')
-            .expect("unexpected number line format");
+            .ok_or_else(|| AocError::unexpected_end(DAY, "stack number line").at_line(1))?;
+        let (_, count) = number_line.trim_end().rsplit_once(' ').ok_or_else(|| {
+            AocError::parse(DAY, "unexpected number line format").at_line(lines.len())
+        })?;
 
-        let num_stacks = count.parse().expect("could not parse number line length");
+        let num_stacks =
+            parse_number(DAY, count, "number line length").map_err(|e| e.at_line(lines.len()))?;
         let max_height = lines.len() - 1;
 
         let mut stacks: Vec<_> = (0..num_stacks)
             .map(|_| Vec::with_capacity(max_height))
             .collect();
 
-        for &line in lines.iter().rev().skip(1) {
-            let mut line = line;
-            for stack in stacks.iter_mut() {
-                if line.starts_with('[') {
-                    line = line.strip_prefix('[').unwrap();
-
-                    let mut iter = line.chars();
-                    let char = iter.next().expect("missing char");
-                    stack.push(char);
-
-                    line = iter.as_str();
-                    line = line.strip_prefix(']').expect("no closing bracket");
-                    line = line.strip_prefix(' ').as_ref().unwrap_or(&line); // if not at end of line
-                } else {
-                    line = line
-                        .strip_prefix("   ")
-                        .expect("unexpected early termination");
-                    line = line.strip_prefix(' ').as_ref().unwrap_or(&line); // if not at end of line
-                }
+        for (i, &line) in lines.iter().enumerate().rev().skip(1) {
+            Stacks::parse_crate_line(line, &mut stacks).map_err(|e| e.at_line(i + 1))?;
+        }
+
+        Ok(Stacks(stacks))
+    }
+
+    /// pushes the crates in a single line of the drawing onto their stacks
+    fn parse_crate_line(line: &str, stacks: &mut [Vec<char>]) -> Result<(), AocError> {
+        let full_line = line;
+        let mut line = line;
+        let column = |line: &str| full_line.len() - line.len() + 1;
+
+        for stack in stacks.iter_mut() {
+            if line.starts_with('[') {
+                line = line.strip_prefix('[').unwrap();
+
+                let mut iter = line.chars();
+                let char = iter.next().ok_or_else(|| {
+                    AocError::unexpected_end(DAY, "crate").at_column(column(line))
+                })?;
+                stack.push(char);
+
+                line = iter.as_str();
+                line = line.strip_prefix(']').ok_or_else(|| {
+                    AocError::parse(DAY, "no closing bracket").at_column(column(line))
+                })?;
+                line = line.strip_prefix(' ').unwrap_or(line); // if not at end of line
+            } else {
+                line = line.strip_prefix("   ").ok_or_else(|| {
+                    AocError::parse(DAY, "unexpected early termination").at_column(column(line))
+                })?;
+                line = line.strip_prefix(' ').unwrap_or(line); // if not at end of line
             }
         }
 
-        Stacks(stacks)
+        Ok(())
     }
 
     fn apply_single_mover(&mut self, rearrangement: &Rearrangement) {
@@ -86,53 +105,78 @@ pub struct Rearrangement {
 }
 
 impl Rearrangement {
-    fn parse_line(input: &str) -> Rearrangement {
+    fn parse_line(input: &str) -> Result<Rearrangement, AocError> {
         let input = input
             .strip_prefix("move ")
-            .expect("rearrangement does not start with 'move '");
+            .ok_or_else(|| AocError::parse(DAY, "rearrangement does not start with 'move '"))?;
         let (amount, input) = input
             .split_once(' ')
-            .expect("could not find ' ' after amount");
-        let amount = amount.parse().expect("could not parse amount");
+            .ok_or_else(|| AocError::parse(DAY, "could not find ' ' after amount"))?;
+        let amount = parse_number(DAY, amount, "amount")?;
 
         let input = input
             .strip_prefix("from ")
-            .expect("rearrangement does not contain 'from '");
+            .ok_or_else(|| AocError::parse(DAY, "rearrangement does not contain 'from '"))?;
         let (origin, input) = input
             .split_once(' ')
-            .expect("could not find ' ' after origin");
-        let origin = origin.parse::<u8>().expect("could not parse origin") - 1u8;
+            .ok_or_else(|| AocError::parse(DAY, "could not find ' ' after origin"))?;
+        let origin = Rearrangement::parse_stack(origin, "origin")?;
 
         let destination = input
             .strip_prefix("to ")
-            .expect("rearrangement does not contain 'to '");
-        let destination = destination
-            .parse::<u8>()
-            .expect("could not parse destination")
-            - 1u8;
+            .ok_or_else(|| AocError::parse(DAY, "rearrangement does not contain 'to '"))?;
+        let destination = Rearrangement::parse_stack(destination, "destination")?;
 
-        Rearrangement {
+        Ok(Rearrangement {
             amount,
             origin,
             destination,
+        })
+    }
+
+    /// parses a 1-based stack number into a 0-based stack index
+    fn parse_stack(s: &str, what: &str) -> Result<u8, AocError> {
+        match parse_number::<u8>(DAY, s, what)? {
+            0 => Err(AocError::parse(
+                DAY,
+                format!("{} stack numbers start at 1", what),
+            )),
+            n => Ok(n - 1),
         }
     }
 }
 
 #[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> (Stacks, Vec<Rearrangement>) {
-    let (stacks, rearrangements) = input
-        .split_once("\n\n")
-        .expect("could not find boundary between stacks and rearrangement");
+pub fn input_generator(input: &str) -> Result<(Stacks, Vec<Rearrangement>), AocError> {
+    let (stacks, rearrangements) = input.split_once("\n\n").ok_or_else(|| {
+        AocError::unexpected_end(DAY, "boundary between stacks and rearrangement")
+    })?;
 
-    let stacks = Stacks::parse(stacks);
+    let first_rearrangement_line = stacks.lines().count() + 2;
+    let stacks = Stacks::parse(stacks)?;
 
     let rearrangements = rearrangements
         .lines()
-        .map(Rearrangement::parse_line)
-        .collect();
+        .enumerate()
+        .map(|(i, line)| {
+            let line_number = first_rearrangement_line + i;
+            let rearrangement =
+                Rearrangement::parse_line(line).map_err(|e| e.at_line(line_number))?;
+
+            for stack in [rearrangement.origin, rearrangement.destination] {
+                if stack as usize >= stacks.0.len() {
+                    return Err(
+                        AocError::parse(DAY, format!("there is no stack {}", stack + 1))
+                            .at_line(line_number),
+                    );
+                }
+            }
+
+            Ok(rearrangement)
+        })
+        .collect::<Result<_, _>>()?;
 
-    (stacks, rearrangements)
+    Ok((stacks, rearrangements))
 }
 
 #[aoc(day5, part1)]
@@ -160,6 +204,29 @@ pub fn follow_rearrangement_multiple_mover(input: &(Stacks, Vec<Rearrangement>))
     stacks.tops()
 }
 
+/// Day 5: Supply Stacks
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = DAY;
+
+    type Input = (Stacks, Vec<Rearrangement>);
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(follow_rearrangement_single_mover(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(follow_rearrangement_multiple_mover(input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,14 +244,14 @@ move 1 from 1 to 2";
 
     #[test]
     fn test_part_one() {
-        let parsed = input_generator(INPUT);
+        let parsed = input_generator(INPUT).unwrap();
         let tops = follow_rearrangement_single_mover(&parsed);
         assert_eq!(tops, "CMZ")
     }
 
     #[test]
     fn test_part_two() {
-        let parsed = input_generator(INPUT);
+        let parsed = input_generator(INPUT).unwrap();
         let tops = follow_rearrangement_multiple_mover(&parsed);
         assert_eq!(tops, "MCD")
     }
@@ -1,77 +1,109 @@
+use crate::error::AocError;
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
+const DAY: u8 = 6;
+
 #[aoc_generator(day6)]
-pub fn input_generator(input: &str) -> String {
-    input.to_owned()
+pub fn input_generator(input: &str) -> Result<String, AocError> {
+    // the detectors index by byte, so only accept single byte characters
+    for (i, line) in input.lines().enumerate() {
+        if i > 0 {
+            return Err(AocError::parse(DAY, "datastream must be a single line").at_line(i + 1));
+        }
+
+        if let Some((j, c)) = line.char_indices().find(|(_, c)| !c.is_ascii_lowercase()) {
+            return Err(
+                AocError::parse(DAY, format!("unexpected datastream character '{}'", c))
+                    .at_line(i + 1)
+                    .at_column(j + 1),
+            );
+        }
+    }
+
+    Ok(input.to_owned())
 }
 
 #[aoc(day6, part1)]
-pub fn start_of_packet_detector(input: &str) -> usize {
+pub fn start_of_packet_detector(input: &str) -> Result<usize, AocError> {
     let mut a = input.chars();
     let mut b = input.chars().skip(1);
     let mut c = input.chars().skip(2);
     let mut d = input.chars().skip(3);
 
-    for current in 4..=input.len() {
-        let a = a.next().unwrap();
-        let b = b.next().unwrap();
-        let c = c.next().unwrap();
-        let d = d.next().unwrap();
+    for current in 4.. {
+        let (Some(a), Some(b), Some(c), Some(d)) = (a.next(), b.next(), c.next(), d.next()) else {
+            break;
+        };
 
         if a != b && a != c && a != d && b != c && b != d && c != d {
-            return current;
+            return Ok(current);
         }
     }
 
-    panic!("no packet start")
+    Err(AocError::solve(DAY, "no start-of-packet marker"))
 }
 
 #[aoc(day6, part2)]
-pub fn start_of_message_detector(input: &str) -> usize {
+pub fn start_of_message_detector(input: &str) -> Result<usize, AocError> {
     let mut remove = input.chars();
     let mut add = input.chars();
 
     let mut map = HashMap::<char, u8>::with_capacity(14);
 
-    for _ in 0..13 {
-        let c = add.next().unwrap();
+    for c in add.by_ref().take(13) {
         map.entry(c)
             .and_modify(|counter| *counter += 1)
             .or_insert(1);
     }
 
-    for current in 14..=input.len() {
-        {
-            let c = add.next().unwrap();
-            map.entry(c)
-                .and_modify(|counter| *counter += 1)
-                .or_insert(1);
-        }
+    for (current, c) in (14..).zip(add) {
+        map.entry(c)
+            .and_modify(|counter| *counter += 1)
+            .or_insert(1);
 
         if map.len() == 14 {
-            return current;
+            return Ok(current);
         }
 
-        {
-            let c = remove.next().unwrap();
-            match map.entry(c) {
-                Entry::Occupied(mut e) => {
-                    match e.get_mut() {
-                        0 => panic!("value cannot be 0"),
-                        1 => {
-                            e.remove_entry();
-                        }
-                        v => *v -= 1,
-                    };
+        // `remove` trails `add`, so its character is always counted
+        if let Some(c) = remove.next() {
+            if let Entry::Occupied(mut e) = map.entry(c) {
+                if *e.get() == 1 {
+                    e.remove_entry();
+                } else {
+                    *e.get_mut() -= 1;
                 }
-                Entry::Vacant(_) => panic!("key is expected"),
             }
         }
     }
 
-    panic!("no message start")
+    Err(AocError::solve(DAY, "no start-of-message marker"))
+}
+
+/// Day 6: Tuning Trouble
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = DAY;
+
+    type Input = String;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        start_of_packet_detector(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        start_of_message_detector(input)
+    }
 }
 
 #[cfg(test)]
@@ -86,19 +118,33 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        assert_eq!(start_of_packet_detector(INPUT_ONE), 7, "one");
-        assert_eq!(start_of_packet_detector(INPUT_TWO), 5, "two");
-        assert_eq!(start_of_packet_detector(INPUT_THREE), 6, "three");
-        assert_eq!(start_of_packet_detector(INPUT_FOUR), 10, "four");
-        assert_eq!(start_of_packet_detector(INPUT_FIVE), 11, "five");
+        assert_eq!(start_of_packet_detector(INPUT_ONE), Ok(7), "one");
+        assert_eq!(start_of_packet_detector(INPUT_TWO), Ok(5), "two");
+        assert_eq!(start_of_packet_detector(INPUT_THREE), Ok(6), "three");
+        assert_eq!(start_of_packet_detector(INPUT_FOUR), Ok(10), "four");
+        assert_eq!(start_of_packet_detector(INPUT_FIVE), Ok(11), "five");
     }
 
     #[test]
     fn test_part_two() {
-        assert_eq!(start_of_message_detector(INPUT_ONE), 19, "one");
-        assert_eq!(start_of_message_detector(INPUT_TWO), 23, "two");
-        assert_eq!(start_of_message_detector(INPUT_THREE), 23, "three");
-        assert_eq!(start_of_message_detector(INPUT_FOUR), 29, "four");
-        assert_eq!(start_of_message_detector(INPUT_FIVE), 26, "five");
+        assert_eq!(start_of_message_detector(INPUT_ONE), Ok(19), "one");
+        assert_eq!(start_of_message_detector(INPUT_TWO), Ok(23), "two");
+        assert_eq!(start_of_message_detector(INPUT_THREE), Ok(23), "three");
+        assert_eq!(start_of_message_detector(INPUT_FOUR), Ok(29), "four");
+        assert_eq!(start_of_message_detector(INPUT_FIVE), Ok(26), "five");
+    }
+
+    #[test]
+    fn test_no_marker() {
+        for input in ["", "abc", "abcabcabc"] {
+            assert_eq!(
+                Day6::part1(&input.to_owned()).unwrap_err().to_string(),
+                "day 6: no start-of-packet marker"
+            );
+        }
+        assert_eq!(
+            start_of_message_detector("abcdefghijklm"),
+            Err(AocError::solve(DAY, "no start-of-message marker"))
+        );
     }
 }
@@ -1,9 +1,13 @@
+use crate::error::{parse_number, AocError};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
+const DAY: u8 = 7;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Node {
     File(File),
@@ -27,10 +31,13 @@ impl Dir {
         }
     }
 
-    fn insert_file(&mut self, name: &str, size: usize) -> Result<&mut File, String> {
+    fn insert_file(&mut self, name: &str, size: usize) -> Result<&mut File, AocError> {
         let entry = self.children.entry(name.to_owned());
         match entry {
-            Entry::Occupied(_) => Err(format!("dir already contains entry for {}", name)),
+            Entry::Occupied(_) => Err(AocError::parse(
+                DAY,
+                format!("dir already contains entry for {}", name),
+            )),
             Entry::Vacant(v) => match v.insert(Node::File(File { size })) {
                 Node::File(file) => Ok(file),
                 Node::Dir(_) => {
@@ -40,10 +47,13 @@ impl Dir {
         }
     }
 
-    fn insert_dir(&mut self, name: &str) -> Result<&mut Dir, String> {
+    fn insert_dir(&mut self, name: &str) -> Result<&mut Dir, AocError> {
         let entry = self.children.entry(name.to_owned());
         match entry {
-            Entry::Occupied(_) => Err(format!("dir already contains entry for {}", name)),
+            Entry::Occupied(_) => Err(AocError::parse(
+                DAY,
+                format!("dir already contains entry for {}", name),
+            )),
             Entry::Vacant(v) => {
                 let children = HashMap::new();
                 match v.insert(Node::Dir(Dir { children })) {
@@ -114,8 +124,8 @@ impl Dir {
 
     fn folder_size(&self) -> usize {
         self.children
-            .iter()
-            .map(|(_, node)| match node {
+            .values()
+            .map(|node| match node {
                 Node::File(File { size }) => *size,
                 Node::Dir(dir) => dir.folder_size(),
             })
@@ -203,7 +213,7 @@ enum Command<'a> {
 }
 
 impl<'a> TryFrom<&'a str> for Command<'a> {
-    type Error = String;
+    type Error = AocError;
 
     fn try_from(command: &'a str) -> Result<Self, Self::Error> {
         if command == "$ ls" {
@@ -211,22 +221,34 @@ impl<'a> TryFrom<&'a str> for Command<'a> {
         } else if let Some(target) = command.strip_prefix("$ cd ") {
             Ok(Command::Cd(CdTarget::from(target)))
         } else {
-            Err(format!("unrecognized command: '{}'", command))
+            Err(AocError::parse(
+                DAY,
+                format!("unrecognized command: '{}'", command),
+            ))
         }
     }
 }
 
 #[aoc_generator(day7)]
-pub fn parse_root_from_commands(input: &str) -> Dir {
-    let mut lines = input.lines().peekable();
+pub fn parse_root_from_commands(input: &str) -> Result<Dir, AocError> {
+    let mut lines = input.lines().enumerate().peekable();
+
+    match lines.next() {
+        Some((_, "$ cd /")) => {}
+        Some((i, line)) => {
+            return Err(
+                AocError::parse(DAY, format!("expected '$ cd /', not '{}'", line)).at_line(i + 1),
+            )
+        }
+        None => return Err(AocError::unexpected_end(DAY, "'$ cd /'").at_line(1)),
+    }
 
-    assert_eq!(lines.next(), Some("$ cd /"));
     let mut root = Dir::root();
     let mut path: Vec<String> = Vec::new();
     let mut current: &mut Dir = &mut root;
 
-    while let Some(command) = lines.next() {
-        let command = Command::try_from(command).expect("could not parse input");
+    while let Some((i, command)) = lines.next() {
+        let command = Command::try_from(command).map_err(|e| e.at_line(i + 1))?;
         match command {
             Command::Cd(target) => {
                 match target {
@@ -240,8 +262,17 @@ pub fn parse_root_from_commands(input: &str) -> Dir {
                         current = root.get_recursive_dir_mut(&path);
                     }
                     CdTarget::Child(child) => match current.get_child_mut(child) {
-                        None => panic!("no child for {}", child),
-                        Some(Node::File(_)) => panic!("expected dir, found file '{}'", child),
+                        None => {
+                            return Err(AocError::parse(DAY, format!("no child for {}", child))
+                                .at_line(i + 1))
+                        }
+                        Some(Node::File(_)) => {
+                            return Err(AocError::parse(
+                                DAY,
+                                format!("expected dir, found file '{}'", child),
+                            )
+                            .at_line(i + 1))
+                        }
                         Some(Node::Dir(dir)) => {
                             path.push(child.to_owned());
                             current = dir;
@@ -250,26 +281,30 @@ pub fn parse_root_from_commands(input: &str) -> Dir {
                 };
             }
             Command::List => {
-                while let Some(line) = lines.peek() {
+                while let Some((_, line)) = lines.peek() {
                     if line.starts_with('$') {
                         break;
                     }
 
-                    let line = lines.next().unwrap();
-                    if let Some(dir) = line.strip_prefix("dir ") {
-                        current.insert_dir(dir).unwrap();
+                    let (i, line) = lines.next().unwrap();
+                    let listed = if let Some(dir) = line.strip_prefix("dir ") {
+                        current.insert_dir(dir).map(|_| ())
                     } else if let Some((size, name)) = line.split_once(' ') {
-                        let size = size.parse::<usize>().unwrap();
-                        current.insert_file(name, size).unwrap();
+                        parse_number(DAY, size, "file size")
+                            .and_then(|size| current.insert_file(name, size).map(|_| ()))
                     } else {
-                        panic!("unexpected ls line: '{}'", line);
-                    }
+                        Err(AocError::parse(
+                            DAY,
+                            format!("unexpected ls line: '{}'", line),
+                        ))
+                    };
+                    listed.map_err(|e| e.at_line(i + 1))?;
                 }
             }
         }
     }
 
-    root
+    Ok(root)
 }
 
 #[aoc(day7, part1)]
@@ -279,18 +314,62 @@ pub fn sum_folder_sizes_below_100000(root: &Dir) -> usize {
 }
 
 #[aoc(day7, part2)]
-pub fn smallest_folder_to_delete_for_update(root: &Dir) -> String {
+pub fn smallest_folder_to_delete_for_update(root: &Dir) -> Result<String, AocError> {
     const TOTAL_DISK_AVAILABLE: usize = 70_000_000;
     const FREE_SPACE_REQUIRED: usize = 30_000_000;
 
     let total_size = root.folder_size();
-    let free_space = TOTAL_DISK_AVAILABLE - total_size;
+    let free_space = TOTAL_DISK_AVAILABLE
+        .checked_sub(total_size)
+        .ok_or_else(|| {
+            AocError::solve(
+                DAY,
+                format!(
+                    "files take up {}, more than the disk's {}",
+                    total_size, TOTAL_DISK_AVAILABLE
+                ),
+            )
+        })?;
+    if free_space >= FREE_SPACE_REQUIRED {
+        return Ok(format!("nothing, {} is already free", free_space));
+    }
     let space_to_clear = FREE_SPACE_REQUIRED - free_space;
 
     let (name, size) = root
         .smallest_folder_at_least(space_to_clear)
-        .expect("could not find folder above size 30_000_000");
-    format!("folder '{}' of size {}", name.unwrap_or("/"), size)
+        .map_err(|total| {
+            AocError::solve(
+                DAY,
+                format!(
+                    "no folder frees {}, only {} in total",
+                    space_to_clear, total
+                ),
+            )
+        })?;
+    Ok(format!("folder '{}' of size {}", name.unwrap_or("/"), size))
+}
+
+/// Day 7: No Space Left On Device
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = DAY;
+
+    type Input = Dir;
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_root_from_commands(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(sum_folder_sizes_below_100000(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        smallest_folder_to_delete_for_update(input)
+    }
 }
 
 #[cfg(test)]
@@ -341,13 +420,13 @@ $ ls
     - k (file, size=7214296)
 ";
 
-        let root = parse_root_from_commands(INPUT);
+        let root = parse_root_from_commands(INPUT).unwrap();
         assert_eq!(format!("{}", root), EXPECTED);
     }
 
     #[test]
     fn test_folder_size() {
-        let root = parse_root_from_commands(INPUT);
+        let root = parse_root_from_commands(INPUT).unwrap();
         assert_eq!(root.folder_size(), 48381165);
         assert_eq!(root._get_recursive_dir(&["d",]).folder_size(), 24933642);
         assert_eq!(root._get_recursive_dir(&["a",]).folder_size(), 94853);
@@ -356,15 +435,30 @@ $ ls
 
     #[test]
     fn test_part_one() {
-        let root = parse_root_from_commands(INPUT);
+        let root = parse_root_from_commands(INPUT).unwrap();
         let sum = sum_folder_sizes_below_100000(&root);
         assert_eq!(sum, 95437);
     }
 
     #[test]
     fn test_part_two() {
-        let root = parse_root_from_commands(INPUT);
+        let root = parse_root_from_commands(INPUT).unwrap();
         let result = smallest_folder_to_delete_for_update(&root);
-        assert_eq!(result, "folder 'd' of size 24933642");
+        assert_eq!(result.as_deref(), Ok("folder 'd' of size 24933642"));
+    }
+
+    #[test]
+    fn test_disk_sizes() {
+        let root = parse_root_from_commands("$ cd /\n$ ls\n100 a").unwrap();
+        assert_eq!(
+            smallest_folder_to_delete_for_update(&root).as_deref(),
+            Ok("nothing, 69999900 is already free")
+        );
+
+        let root = parse_root_from_commands("$ cd /\n$ ls\n70000001 a").unwrap();
+        assert_eq!(
+            Day7::part2(&root).unwrap_err().to_string(),
+            "day 7: files take up 70000001, more than the disk's 70000000"
+        );
     }
 }
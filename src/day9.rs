@@ -1,23 +1,53 @@
+use crate::error::{parse_lines, parse_number, AocError};
+use crate::grid::{Grid, Position};
+use crate::ppm::{lerp, Rgb};
+use crate::solutions::Solution;
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::cmp::{max, min};
+use std::cmp::max;
+use std::collections::HashSet;
 use std::ops::Range;
+use std::slice;
+
+const DAY: u8 = 9;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Move {
-    direction: Direction,
-    steps: u8,
+    pub direction: Direction,
+    pub steps: u32,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
     Up,
     Down,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// the `(x, y)` change of one step in this direction
+    fn offset(self) -> (i64, i64) {
+        use Direction::*;
+
+        match self {
+            Left => (-1, 0),
+            Right => (1, 0),
+            Up => (0, 1),
+            Down => (0, -1),
+            UpLeft => (-1, 1),
+            UpRight => (1, 1),
+            DownLeft => (-1, -1),
+            DownRight => (1, -1),
+        }
+    }
 }
 
 impl TryFrom<&str> for Direction {
-    type Error = String;
+    type Error = AocError;
 
     fn try_from(c: &str) -> Result<Self, Self::Error> {
         use Direction::*;
@@ -27,157 +57,427 @@ impl TryFrom<&str> for Direction {
             "R" => Ok(Right),
             "U" => Ok(Up),
             "D" => Ok(Down),
-            _ => Err(format!("invalid direction character: '{}'", c)),
+            "UL" => Ok(UpLeft),
+            "UR" => Ok(UpRight),
+            "DL" => Ok(DownLeft),
+            "DR" => Ok(DownRight),
+            _ => Err(AocError::parse(DAY, format!("invalid direction: '{}'", c))),
         }
     }
 }
 
 #[aoc_generator(day9)]
-pub fn parse_moves(input: &str) -> Vec<Move> {
-    input
-        .lines()
-        .map(|input| {
-            let (direction, steps) = input.split_once(' ').expect("unexpected move format");
-            let direction = direction.try_into().expect("unable to parse direction");
-            let steps = steps.parse().expect("unable to parse amount");
-            Move { direction, steps }
-        })
-        .collect()
+pub fn parse_moves(input: &str) -> Result<Vec<Move>, AocError> {
+    parse_lines(input, |input| {
+        let (direction, steps) = input
+            .split_once(' ')
+            .ok_or_else(|| AocError::parse(DAY, "unexpected move format"))?;
+        let direction = direction.try_into().map_err(|e: AocError| e.at_column(1))?;
+        let steps = parse_number(DAY, steps, "amount")
+            .map_err(|e| e.at_column(input.len() - steps.len() + 1))?;
+        Ok(Move { direction, steps })
+    })
 }
 
-fn get_ranges(moves: &[Move]) -> (Range<i32>, Range<i32>) {
-    let mut x: i32 = 0;
-    let mut y: i32 = 0;
+/// a knot's position relative to where the rope starts, with `x` increasing right and `y` up.
+/// 64-bit, as a single move of up to `u32::MAX` steps could overflow 32 bits.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Coordinate {
+    pub x: i64,
+    pub y: i64,
+}
 
-    let mut x_range = 0..1;
-    let mut y_range = 0..1;
+impl Coordinate {
+    fn move_towards(&mut self, direction: Direction) {
+        let (x, y) = direction.offset();
+        self.x += x;
+        self.y += y;
+    }
 
-    for mv in moves {
-        match mv.direction {
-            Direction::Left => {
-                x += mv.steps as i32;
-                x_range.end = max(x_range.end, x + 1);
-            }
-            Direction::Right => {
-                x -= mv.steps as i32;
-                x_range.start = min(x_range.start, x);
-            }
-            Direction::Up => {
-                y += mv.steps as i32;
-                y_range.end = max(y_range.end, y + 1);
-            }
-            Direction::Down => {
-                y -= mv.steps as i32;
-                y_range.start = min(y_range.start, y);
-            }
-        };
+    fn diff(&self, tail: &Coordinate) -> (i64, i64) {
+        (self.x - tail.x, self.y - tail.y)
     }
 
-    (x_range, y_range)
+    /// moves one step (straight or diagonally) towards `leader`, unless it's already touching it.
+    /// returns whether it moved.
+    fn follow(&mut self, leader: &Coordinate) -> bool {
+        let (diff_x, diff_y) = leader.diff(self);
+        if diff_x.abs() <= 1 && diff_y.abs() <= 1 {
+            return false;
+        }
+
+        self.x += diff_x.signum();
+        self.y += diff_y.signum();
+        true
+    }
 }
 
+/// how far a knot travelled over a simulation
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Coordinate {
-    x: usize,
-    y: usize,
+pub struct KnotReport {
+    /// how many distinct positions it has been in, including the start
+    pub visited: usize,
+    /// how many cells it moved
+    pub moves: usize,
 }
 
-impl Coordinate {
-    fn move_towards(&mut self, direction: Direction) {
-        match direction {
-            Direction::Left => self.x -= 1,
-            Direction::Right => self.x += 1,
-            Direction::Up => self.y += 1,
-            Direction::Down => self.y -= 1,
-        };
+/// a rope of knots starting on top of each other, where the first knot is the head and every other
+/// knot follows the one before it.
+///
+/// each knot has a speed: how many cells it can move each time the head steps. the head moves its
+/// speed in cells, and every other knot takes up to its speed in steps towards the knot before it.
+#[derive(Debug, Clone)]
+pub struct Rope {
+    knots: Vec<Coordinate>,
+    speeds: Vec<u32>,
+    visited: Vec<HashSet<Coordinate>>,
+    moves: Vec<usize>,
+}
+
+impl Rope {
+    /// a rope of `num_knots` knots that each move one cell at a time, as in the puzzle
+    pub fn new(num_knots: usize) -> Rope {
+        Rope::with_speeds(vec![1; num_knots])
+    }
+
+    /// a rope with a knot for each of `speeds`, from the head down
+    pub fn with_speeds(speeds: Vec<u32>) -> Rope {
+        let num_knots = speeds.len();
+        assert!(num_knots > 0, "a rope needs at least one knot");
+        Rope {
+            knots: vec![Coordinate::default(); num_knots],
+            speeds,
+            visited: vec![HashSet::from([Coordinate::default()]); num_knots],
+            moves: vec![0; num_knots],
+        }
+    }
+
+    /// a rope of `num_knots` knots after the head makes `moves`
+    pub fn simulate(num_knots: usize, moves: &[Move]) -> Rope {
+        let mut rope = Rope::new(num_knots);
+        moves.iter().for_each(|mv| rope.apply(mv));
+        rope
+    }
+
+    pub fn knots(&self) -> &[Coordinate] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> Coordinate {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Coordinate {
+        self.knots[self.knots.len() - 1]
     }
 
-    fn diff(&self, tail: &Coordinate) -> (i8, i8) {
-        let diff_x = (self.x as isize - tail.x as isize) as i8;
-        let diff_y = (self.y as isize - tail.y as isize) as i8;
-        (diff_x, diff_y)
-    }
-
-    fn follow(&mut self, diff_x: i8, diff_y: i8) {
-        match (diff_x, diff_y) {
-            // on top of each other
-            (0, 0) => (),
-            // already adjacent
-            (0, 1) | (0, -1) | (1, 0) | (-1, 0) | (1, 1) | (1, -1) | (-1, -1) | (-1, 1) => (),
-            // one directional
-            (0, 2) => self.y += 1,
-            (0, -2) => self.y -= 1,
-            (2, 0) => self.x += 1,
-            (-2, 0) => self.x -= 1,
-            // diagonal
-            (1, 2) | (2, 1) | (2, 2) => {
-                self.x += 1;
-                self.y += 1;
+    /// moves the head one step in `direction`, with the rest of the rope following
+    pub fn step(&mut self, direction: Direction) {
+        let unit_speeds = self.speeds.iter().all(|&speed| speed == 1);
+        for i in 0..self.knots.len() {
+            let mut moved = false;
+            for _ in 0..self.speeds[i] {
+                if i == 0 {
+                    self.knots[0].move_towards(direction);
+                } else {
+                    let leader = self.knots[i - 1];
+                    if !self.knots[i].follow(&leader) {
+                        break;
+                    }
+                }
+
+                moved = true;
+                self.visited[i].insert(self.knots[i]);
+                self.moves[i] += 1;
             }
-            (1, -2) | (2, -1) | (2, -2) => {
-                self.x += 1;
-                self.y -= 1;
+
+            if !moved && unit_speeds {
+                // nothing further down the rope will move either. with mixed speeds a slow knot
+                // may still be catching up with one that has stopped, so it has to keep going.
+                break;
             }
-            (-1, -2) | (-2, -1) | (-2, -2) => {
-                self.x -= 1;
-                self.y -= 1;
+        }
+    }
+
+    pub fn apply(&mut self, mv: &Move) {
+        for _ in 0..mv.steps {
+            self.step(mv.direction);
+        }
+    }
+
+    /// how far each knot has travelled, from head to tail
+    pub fn reports(&self) -> Vec<KnotReport> {
+        self.visited
+            .iter()
+            .zip(&self.moves)
+            .map(|(visited, &moves)| KnotReport {
+                visited: visited.len(),
+                moves,
+            })
+            .collect()
+    }
+
+    /// how many distinct positions the tail has been in
+    pub fn tail_visited(&self) -> usize {
+        self.visited[self.visited.len() - 1].len()
+    }
+
+    /// every position knot `knot` (0 being the head) has been in
+    pub fn visited(&self, knot: usize) -> &HashSet<Coordinate> {
+        &self.visited[knot]
+    }
+}
+
+/// the part of the plane a frame shows
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Window {
+    pub x_range: Range<i64>,
+    pub y_range: Range<i64>,
+}
+
+impl Window {
+    pub fn centred_on(centre: Coordinate, width: usize, height: usize) -> Window {
+        assert!(width > 0 && height > 0, "empty window");
+        let (width, height) = (width as i64, height as i64);
+        let x = centre.x - width / 2;
+        let y = centre.y - height / 2;
+        Window {
+            x_range: x..x + width,
+            y_range: y..y + height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (self.x_range.end - self.x_range.start) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.y_range.end - self.y_range.start) as usize
+    }
+
+    pub fn contains(&self, point: Coordinate) -> bool {
+        self.x_range.contains(&point.x) && self.y_range.contains(&point.y)
+    }
+
+    /// shifts the window as little as possible to bring `point` into view
+    pub fn follow(&mut self, point: Coordinate) {
+        fn shift(range: &mut Range<i64>, to: i64) {
+            let by = if to < range.start {
+                to - range.start
+            } else if to >= range.end {
+                to - range.end + 1
+            } else {
+                0
+            };
+            *range = range.start + by..range.end + by;
+        }
+
+        shift(&mut self.x_range, point.x);
+        shift(&mut self.y_range, point.y);
+    }
+
+    /// the `(row, col)` of `point` in the window, with the highest `y` in the top row
+    fn position(&self, point: Coordinate) -> Option<Position> {
+        self.contains(point).then(|| {
+            let row = (self.y_range.end - 1 - point.y) as usize;
+            let col = (point.x - self.x_range.start) as usize;
+            (row, col)
+        })
+    }
+}
+
+/// a snapshot of a rope partway through its moves
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    /// how many steps the head has taken
+    pub step: usize,
+    pub rope: &'a Rope,
+    pub window: &'a Window,
+}
+
+const EMPTY: Rgb = [20, 20, 30];
+const TAIL_VISITED: Rgb = [80, 80, 100];
+const START: Rgb = [230, 200, 60];
+const HEAD: Rgb = [230, 50, 50];
+const TAIL: Rgb = [60, 120, 230];
+
+impl Frame<'_> {
+    /// the cells drawn for each knot, from the head down, then the start and the tail's trail
+    fn layers(&self) -> impl Iterator<Item = (Coordinate, Layer)> + '_ {
+        let num_knots = self.rope.knots().len();
+        let tail = num_knots - 1;
+        let knots = self.rope.knots().iter().copied().enumerate();
+        let trail = self.rope.visited(tail).iter().copied();
+
+        knots
+            .map(move |(i, knot)| (knot, Layer::Knot(i, num_knots)))
+            .chain([(Coordinate::default(), Layer::Start)])
+            .chain(trail.map(|visited| (visited, Layer::TailVisited)))
+    }
+
+    /// draws the window as in the puzzle, with `H` for the head, `1` to `9` for the following
+    /// knots (or `T` for the tail of a two-knot rope), `s` for the start and `#` for positions the
+    /// tail has visited. knots nearer the head are drawn over knots further down the rope.
+    pub fn to_ascii(&self) -> String {
+        let mut cells = Grid::new(self.window.width(), self.window.height(), '.');
+        let mut drawn = Grid::new(self.window.width(), self.window.height(), false);
+        for (point, layer) in self.layers() {
+            if let Some(position) = self.window.position(point) {
+                if !drawn[position] {
+                    cells[position] = layer.glyph();
+                    drawn[position] = true;
+                }
             }
-            (-1, 2) | (-2, 1) | (-2, 2) => {
-                self.x -= 1;
-                self.y += 1;
+        }
+
+        cells
+            .rows()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// draws the window with each cell as a `scale` by `scale` square, shading knots from red at
+    /// the head to blue at the tail
+    pub fn to_image(&self, scale: usize) -> Grid<Rgb> {
+        assert!(scale > 0, "scale must be positive");
+        let mut cells = Grid::new(self.window.width(), self.window.height(), None);
+        for (point, layer) in self.layers() {
+            if let Some(position) = self.window.position(point) {
+                if cells[position].is_none() {
+                    cells[position] = Some(layer.colour());
+                }
             }
-            _ => panic!("unexpected position diff ({}, {})", diff_x, diff_y),
-        };
+        }
+
+        let mut image = Grid::new(cells.width() * scale, cells.height() * scale, EMPTY);
+        for (row, col) in image.positions() {
+            if let Some(colour) = cells[(row / scale, col / scale)] {
+                image[(row, col)] = colour;
+            }
+        }
+        image
     }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Positions {
-    head: Coordinate,
-    tail: Coordinate,
+enum Layer {
+    /// the knot's index and the number of knots in the rope
+    Knot(usize, usize),
+    Start,
+    TailVisited,
 }
 
-pub fn tail_visited_positions<const CHAIN_LENGTH: usize>(moves: &[Move]) -> usize {
-    let (x_range, y_range) = get_ranges(moves);
+impl Layer {
+    fn glyph(self) -> char {
+        match self {
+            Layer::Knot(0, _) => 'H',
+            Layer::Knot(1, 2) => 'T',
+            Layer::Knot(i @ 1..=9, _) => char::from_digit(i as u32, 10).unwrap(),
+            Layer::Knot(i, len) if i == len - 1 => 'T',
+            Layer::Knot(..) => '+',
+            Layer::Start => 's',
+            Layer::TailVisited => '#',
+        }
+    }
 
-    let x_len = x_range.len();
-    let y_len = y_range.len();
+    fn colour(self) -> Rgb {
+        match self {
+            Layer::Knot(i, len) => lerp(HEAD, TAIL, i as f64 / max(len - 1, 1) as f64),
+            Layer::Start => START,
+            Layer::TailVisited => TAIL_VISITED,
+        }
+    }
+}
 
-    let mut visited = vec![false; x_len * y_len];
+/// steps a rope through its moves one head step at a time, moving a window along with the head
+#[derive(Debug, Clone)]
+pub struct RopeStepper<'a> {
+    rope: Rope,
+    window: Window,
+    moves: slice::Iter<'a, Move>,
+    current: Option<Move>,
+    step: usize,
+}
 
-    let start = Coordinate {
-        x: -x_range.start as usize,
-        y: -y_range.start as usize,
-    };
-    visited[start.x + start.y * y_len] = true;
+impl<'a> RopeStepper<'a> {
+    /// a rope of `num_knots` knots about to make `moves`, shown in a `width` by `height` window
+    /// centred on the start
+    pub fn new(num_knots: usize, moves: &'a [Move], width: usize, height: usize) -> Self {
+        RopeStepper {
+            rope: Rope::new(num_knots),
+            window: Window::centred_on(Coordinate::default(), width, height),
+            moves: moves.iter(),
+            current: None,
+            step: 0,
+        }
+    }
 
-    moves.iter().fold([start; CHAIN_LENGTH], |mut chain, mv| {
-        for _ in 0..mv.steps {
-            chain[0].move_towards(mv.direction);
+    /// the current state, without stepping
+    pub fn frame(&self) -> Frame<'_> {
+        Frame {
+            step: self.step,
+            rope: &self.rope,
+            window: &self.window,
+        }
+    }
 
-            for i in 1..CHAIN_LENGTH {
-                let (diff_x, diff_y) = chain[i - 1].diff(&chain[i]);
-                chain[i].follow(diff_x, diff_y);
+    /// takes the next head step and returns the resulting frame, or `None` once every move has been
+    /// made
+    pub fn next_frame(&mut self) -> Option<Frame<'_>> {
+        let mv = loop {
+            match self.current {
+                Some(mv) if mv.steps > 0 => break mv,
+                _ => self.current = Some(*self.moves.next()?),
             }
+        };
 
-            let tail = chain[CHAIN_LENGTH - 1];
-            visited[tail.x + tail.y * y_len] = true;
-        }
-
-        chain
-    });
+        self.rope.step(mv.direction);
+        self.current = Some(Move {
+            steps: mv.steps - 1,
+            ..mv
+        });
+        self.step += 1;
+        self.window.follow(self.rope.head());
+        Some(self.frame())
+    }
 
-    visited.iter().filter(|v| **v).count()
+    pub fn into_rope(self) -> Rope {
+        self.rope
+    }
 }
 
 #[aoc(day9, part1)]
 pub fn tail_visits_chain_two(moves: &[Move]) -> usize {
-    tail_visited_positions::<2>(moves)
+    Rope::simulate(2, moves).tail_visited()
 }
 
 #[aoc(day9, part2)]
 pub fn tail_visits_chain_ten(moves: &[Move]) -> usize {
-    tail_visited_positions::<10>(moves)
+    Rope::simulate(10, moves).tail_visited()
+}
+
+/// Day 9: Rope Bridge
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = DAY;
+
+    type Input = Vec<Move>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError> {
+        parse_moves(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError> {
+        Ok(tail_visits_chain_two(input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError> {
+        Ok(tail_visits_chain_ten(input))
+    }
 }
 
 #[cfg(test)]
@@ -206,7 +506,7 @@ U 20";
 
     #[test]
     fn test_part1_small() {
-        let moves = parse_moves(INPUT_1);
+        let moves = parse_moves(INPUT_1).unwrap();
         let num_visited = tail_visits_chain_two(&moves);
 
         assert_eq!(num_visited, 13)
@@ -214,7 +514,7 @@ U 20";
 
     #[test]
     fn test_part2_small() {
-        let moves = parse_moves(INPUT_1);
+        let moves = parse_moves(INPUT_1).unwrap();
         let num_visited = tail_visits_chain_ten(&moves);
 
         assert_eq!(num_visited, 1)
@@ -222,9 +522,170 @@ U 20";
 
     #[test]
     fn test_part2_large() {
-        let moves = parse_moves(INPUT_2);
+        let moves = parse_moves(INPUT_2).unwrap();
         let num_visited = tail_visits_chain_ten(&moves);
 
         assert_eq!(num_visited, 36)
     }
+
+    #[test]
+    fn test_moves_below_and_left_of_start() {
+        let moves = parse_moves("L 3\nD 300\nR 1").unwrap();
+        assert_eq!(tail_visits_chain_two(&moves), 302);
+        assert_eq!(tail_visits_chain_ten(&moves), 292);
+    }
+
+    #[test]
+    fn test_rope_reports() {
+        let moves = parse_moves(INPUT_2).unwrap();
+        let rope = Rope::simulate(10, &moves);
+        let reports = rope.reports();
+
+        assert_eq!(reports.len(), 10);
+        assert_eq!(reports[0].moves, 5 + 8 + 8 + 3 + 17 + 10 + 25 + 20);
+        assert_eq!(reports[9].visited, 36);
+        assert_eq!(rope.head(), Coordinate { x: -11, y: 15 });
+        assert_eq!(rope.tail(), Coordinate { x: -11, y: 6 });
+        // a knot can only move when the one before it does
+        assert!(reports.windows(2).all(|w| w[0].moves >= w[1].moves));
+
+        let rope = Rope::simulate(1, &moves);
+        assert_eq!(rope.tail_visited(), rope.reports()[0].visited);
+    }
+
+    #[test]
+    fn test_frames_match_puzzle_diagrams() {
+        let moves = parse_moves(INPUT_1).unwrap();
+        let mut stepper = RopeStepper::new(2, &moves, 6, 5);
+        // the example's grid has the start in the bottom left
+        stepper.window = Window {
+            x_range: 0..6,
+            y_range: 0..5,
+        };
+
+        assert_eq!(
+            stepper.frame().to_ascii(),
+            "......\n......\n......\n......\nH....."
+        );
+        for _ in 0..4 {
+            stepper.next_frame();
+        }
+        let frame = stepper.next_frame().unwrap();
+        assert_eq!(frame.step, 5);
+        assert_eq!(frame.to_ascii(), "......\n......\n......\n....H.\ns##T..");
+    }
+
+    #[test]
+    fn test_stepper_follows_head() {
+        let moves = parse_moves(INPUT_2).unwrap();
+        let mut stepper = RopeStepper::new(10, &moves, 11, 11);
+        let mut frames = 0;
+        while let Some(frame) = stepper.next_frame() {
+            assert!(frame.window.contains(frame.rope.head()));
+            assert_eq!(frame.to_ascii().chars().filter(|&c| c == 'H').count(), 1);
+            frames += 1;
+        }
+        assert_eq!(frames, 5 + 8 + 8 + 3 + 17 + 10 + 25 + 20);
+        assert_eq!(stepper.into_rope().tail_visited(), 36);
+    }
+
+    #[test]
+    fn test_frame_image() {
+        let moves = parse_moves("R 2").unwrap();
+        let mut stepper = RopeStepper::new(2, &moves, 3, 1);
+        let frame = stepper.next_frame().unwrap();
+        let image = frame.to_image(2);
+
+        assert_eq!((image.width(), image.height()), (6, 2));
+        // the window is centred on the start, so the head is in the right hand cell
+        assert_eq!(image[(1, 5)], HEAD);
+        assert_eq!(image[(0, 3)], TAIL);
+        assert_eq!(image[(0, 0)], EMPTY);
+    }
+
+    #[test]
+    fn test_diagonal_moves() {
+        let moves = parse_moves("UR 3\nDL 1\nDR 2\nUL 2").unwrap();
+        assert_eq!(
+            moves[0],
+            Move {
+                direction: Direction::UpRight,
+                steps: 3
+            }
+        );
+
+        let rope = Rope::simulate(2, &moves);
+        assert_eq!(rope.head(), Coordinate { x: 2, y: 2 });
+        assert_eq!(rope.tail(), Coordinate { x: 3, y: 1 });
+        assert_eq!(rope.reports()[1].visited, 4);
+
+        let error = parse_moves("R 1\nRU 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 9, line 2, column 1: invalid direction: 'RU'"
+        );
+    }
+
+    #[test]
+    fn test_fast_knots() {
+        // the head runs two cells per step, so a normal knot falls ever further behind
+        let moves = parse_moves("R 4").unwrap();
+        let rope = Rope::simulate(1, &moves);
+        let fast = {
+            let mut rope = Rope::with_speeds(vec![2, 1]);
+            rope.apply(&moves[0]);
+            rope
+        };
+        assert_eq!(rope.head(), Coordinate { x: 4, y: 0 });
+        assert_eq!(fast.head(), Coordinate { x: 8, y: 0 });
+        assert_eq!(fast.tail(), Coordinate { x: 4, y: 0 });
+
+        // a fast knot catches up however far behind it is
+        let mut rope = Rope::with_speeds(vec![2, 1, 9]);
+        rope.apply(&moves[0]);
+        assert_eq!(rope.knots()[2], Coordinate { x: 3, y: 0 });
+        assert_eq!(
+            rope.reports(),
+            vec![
+                KnotReport {
+                    visited: 9,
+                    moves: 8
+                },
+                KnotReport {
+                    visited: 5,
+                    moves: 4
+                },
+                KnotReport {
+                    visited: 4,
+                    moves: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slow_knots_catch_up_after_reversing() {
+        // the second knot stops once the head turns back, but the slow tail is still behind it
+        let mut rope = Rope::with_speeds(vec![2, 2, 1]);
+        for mv in parse_moves("R 4\nL 1").unwrap() {
+            rope.apply(&mv);
+        }
+        let xs = rope.knots().iter().map(|knot| knot.x).collect::<Vec<_>>();
+        assert_eq!(xs, [6, 7, 4]);
+    }
+
+    #[test]
+    fn test_beyond_32_bits() {
+        let edge = i32::MAX as i64;
+        let mut head = Coordinate { x: edge, y: 0 };
+        let mut tail = Coordinate { x: edge - 1, y: 0 };
+        head.move_towards(Direction::Right);
+        assert!(tail.follow(&head));
+        assert_eq!((head.x, tail.x), (edge + 1, edge));
+
+        let mut window = Window::centred_on(Coordinate::default(), 3, 3);
+        window.follow(head);
+        assert_eq!(window.x_range, edge - 1..edge + 2);
+        assert_eq!(window.width(), 3);
+    }
 }
@@ -0,0 +1,192 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An error found while parsing a day's puzzle input, or solving it.
+///
+/// Errors are created with the day they belong to, and the line (and, where known, column) is
+/// attached as they propagate out of the line parser that produced them. Lines and columns are
+/// both 1-based.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AocError {
+    /// part of the input did not match the expected format
+    Parse {
+        day: u8,
+        line: Option<usize>,
+        column: Option<usize>,
+        reason: String,
+    },
+    /// the input ended before something that was expected
+    UnexpectedEnd {
+        day: u8,
+        line: Option<usize>,
+        column: Option<usize>,
+        expected: String,
+    },
+    /// the input parsed, but a part couldn't be solved for it
+    Solve { day: u8, reason: String },
+}
+
+impl AocError {
+    pub fn parse<S: Into<String>>(day: u8, reason: S) -> AocError {
+        AocError::Parse {
+            day,
+            line: None,
+            column: None,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn unexpected_end<S: Into<String>>(day: u8, expected: S) -> AocError {
+        AocError::UnexpectedEnd {
+            day,
+            line: None,
+            column: None,
+            expected: expected.into(),
+        }
+    }
+
+    pub fn solve<S: Into<String>>(day: u8, reason: S) -> AocError {
+        AocError::Solve {
+            day,
+            reason: reason.into(),
+        }
+    }
+
+    /// attaches a line number, unless the error already has one or isn't about the input's text
+    pub fn at_line(mut self, line: usize) -> AocError {
+        match &mut self {
+            AocError::Parse { line: l, .. } | AocError::UnexpectedEnd { line: l, .. } => {
+                l.get_or_insert(line);
+            }
+            AocError::Solve { .. } => {}
+        };
+        self
+    }
+
+    /// attaches a column number, unless the error already has one or isn't about the input's text
+    pub fn at_column(mut self, column: usize) -> AocError {
+        match &mut self {
+            AocError::Parse { column: c, .. } | AocError::UnexpectedEnd { column: c, .. } => {
+                c.get_or_insert(column);
+            }
+            AocError::Solve { .. } => {}
+        };
+        self
+    }
+
+    pub fn day(&self) -> u8 {
+        match self {
+            AocError::Parse { day, .. }
+            | AocError::UnexpectedEnd { day, .. }
+            | AocError::Solve { day, .. } => *day,
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            AocError::Parse { line, .. } | AocError::UnexpectedEnd { line, .. } => *line,
+            AocError::Solve { .. } => None,
+        }
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            AocError::Parse { column, .. } | AocError::UnexpectedEnd { column, .. } => *column,
+            AocError::Solve { .. } => None,
+        }
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {}", self.day())?;
+        if let Some(line) = self.line() {
+            write!(f, ", line {}", line)?;
+        }
+        if let Some(column) = self.column() {
+            write!(f, ", column {}", column)?;
+        }
+
+        match self {
+            AocError::Parse { reason, .. } | AocError::Solve { reason, .. } => {
+                write!(f, ": {}", reason)
+            }
+            AocError::UnexpectedEnd { expected, .. } => {
+                write!(f, ": unexpected end of input, expected {}", expected)
+            }
+        }
+    }
+}
+
+impl Error for AocError {}
+
+/// parses `s` as a number, describing it as `what` if it cannot be parsed
+pub(crate) fn parse_number<T>(day: u8, s: &str, what: &str) -> Result<T, AocError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| AocError::parse(day, format!("could not parse {} '{}': {}", what, s, e)))
+}
+
+/// parses every line of `input` with `parse`, attaching the line number to any error
+pub(crate) fn parse_lines<T, F>(input: &str, mut parse: F) -> Result<Vec<T>, AocError>
+where
+    F: FnMut(&str) -> Result<T, AocError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|e| e.at_line(i + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = AocError::parse(11, "invalid operation '-'").at_line(4);
+        assert_eq!(error.to_string(), "day 11, line 4: invalid operation '-'");
+
+        let error = AocError::parse(2, "unrecognized game result character 'W'")
+            .at_column(3)
+            .at_line(7);
+        assert_eq!(
+            error.to_string(),
+            "day 2, line 7, column 3: unrecognized game result character 'W'"
+        );
+
+        let error = AocError::unexpected_end(5, "rearrangements");
+        assert_eq!(
+            error.to_string(),
+            "day 5: unexpected end of input, expected rearrangements"
+        );
+
+        let error = AocError::solve(11, "worry level went below zero: 50 - 100").at_line(3);
+        assert_eq!(
+            error.to_string(),
+            "day 11: worry level went below zero: 50 - 100"
+        );
+    }
+
+    #[test]
+    fn test_location_is_not_overwritten() {
+        let error = AocError::parse(13, "expected ','").at_line(2).at_line(5);
+        assert_eq!(error.line(), Some(2));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let parsed = parse_lines("1\n2\nx\n4", |line| parse_number::<u32>(1, line, "value"));
+        let error = parsed.unwrap_err();
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(
+            error.to_string(),
+            "day 1, line 3: could not parse value 'x': invalid digit found in string"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use crate::error::AocError;
+use std::iter::StepBy;
+use std::ops::{Index, IndexMut};
+use std::slice::{ChunksExact, ChunksExactMut, Iter};
+
+/// A `(row, col)` position in a grid, with `(0, 0)` in the top left.
+pub type Position = (usize, usize);
+
+/// A rectangular grid of cells, stored in row-major order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// creates a grid with every cell set to `value`
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        assert!(width > 0, "grid must have at least one column");
+        Grid {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// creates a grid from cells in row-major order
+    pub fn from_vec(cells: Vec<T>, width: usize) -> Grid<T> {
+        assert!(width > 0, "grid must have at least one column");
+        assert_eq!(cells.len() % width, 0, "cells must fill every row");
+
+        let height = cells.len() / width;
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// parses a map with one character per cell and one line per row, attributing any error to
+    /// the line and column of the character that caused it
+    pub fn parse_char_map<F>(day: u8, input: &str, mut parse: F) -> Result<Grid<T>, AocError>
+    where
+        F: FnMut(Position, char) -> Result<T, AocError>,
+    {
+        let width = input
+            .lines()
+            .next()
+            .ok_or_else(|| AocError::unexpected_end(day, "at least one row").at_line(1))?
+            .chars()
+            .count();
+        if width == 0 {
+            return Err(AocError::parse(day, "grid must have at least one column").at_line(1));
+        }
+
+        let mut cells = Vec::with_capacity(input.len());
+        for (row, line) in input.lines().enumerate() {
+            let mut len = 0;
+            for (col, c) in line.chars().enumerate() {
+                let cell =
+                    parse((row, col), c).map_err(|e| e.at_line(row + 1).at_column(col + 1))?;
+                cells.push(cell);
+                len += 1;
+            }
+
+            if len != width {
+                return Err(AocError::parse(
+                    day,
+                    format!("expected {} columns, found {}", width, len),
+                )
+                .at_line(row + 1));
+            }
+        }
+
+        Ok(Grid::from_vec(cells, width))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (row, col): Position) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        if self.contains(position) {
+            Some(&self.cells[self.index(position)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        if self.contains(position) {
+            let index = self.index(position);
+            Some(&mut self.cells[index])
+        } else {
+            None
+        }
+    }
+
+    fn index(&self, (row, col): Position) -> usize {
+        row * self.width + col
+    }
+
+    /// all cells, in row-major order
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// all positions, in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> ChunksExact<'_, T> {
+        self.cells.chunks_exact(self.width)
+    }
+
+    pub fn rows_mut(&mut self) -> ChunksExactMut<'_, T> {
+        self.cells.chunks_exact_mut(self.width)
+    }
+
+    /// the cells of a column, from top to bottom
+    pub fn column(&self, col: usize) -> StepBy<Iter<'_, T>> {
+        assert!(col < self.width, "column {} is out of range", col);
+        self.cells[col..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = StepBy<Iter<'_, T>>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    /// the position `offset` (in rows, cols) away, if it is within the grid
+    pub fn offset(&self, (row, col): Position, (d_row, d_col): (isize, isize)) -> Option<Position> {
+        let position = (
+            row.checked_add_signed(d_row)?,
+            col.checked_add_signed(d_col)?,
+        );
+        self.contains(position).then_some(position)
+    }
+
+    /// the (up to) 4 orthogonally adjacent positions
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        const OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+        OFFSETS
+            .into_iter()
+            .filter_map(move |offset| self.offset(position, offset))
+    }
+
+    /// the (up to) 8 orthogonally or diagonally adjacent positions
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ];
+        OFFSETS
+            .into_iter()
+            .filter_map(move |offset| self.offset(position, offset))
+    }
+
+    /// a view of the grid that can be transposed, flipped or rotated without copying
+    pub fn view(&self) -> View<'_, T> {
+        View {
+            grid: self,
+            transpose: false,
+            flip_rows: false,
+            flip_cols: false,
+        }
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &Self::Output {
+        self.get(position)
+            .unwrap_or_else(|| panic!("position {:?} is out of range", position))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut Self::Output {
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("position {:?} is out of range", position))
+    }
+}
+
+/// A read-only, re-oriented view of a [`Grid`].
+///
+/// Positions passed to a view are in the view's own orientation; [`View::grid_position`]
+/// translates them back to the underlying grid.
+#[derive(Debug)]
+pub struct View<'a, T> {
+    grid: &'a Grid<T>,
+    // applied to a view position in this order: flip rows, flip cols, then transpose
+    transpose: bool,
+    flip_rows: bool,
+    flip_cols: bool,
+}
+
+// derived impls would require `T: Clone`/`T: Copy`
+impl<T> Clone for View<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for View<'_, T> {}
+
+impl<'a, T> View<'a, T> {
+    pub fn width(&self) -> usize {
+        if self.transpose {
+            self.grid.height
+        } else {
+            self.grid.width
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        if self.transpose {
+            self.grid.width
+        } else {
+            self.grid.height
+        }
+    }
+
+    /// swaps rows and columns
+    pub fn transposed(self) -> View<'a, T> {
+        View {
+            transpose: !self.transpose,
+            flip_rows: self.flip_cols,
+            flip_cols: self.flip_rows,
+            ..self
+        }
+    }
+
+    /// reverses the order of the rows
+    pub fn flipped_vertically(self) -> View<'a, T> {
+        View {
+            flip_rows: !self.flip_rows,
+            ..self
+        }
+    }
+
+    /// reverses the order of the columns
+    pub fn flipped_horizontally(self) -> View<'a, T> {
+        View {
+            flip_cols: !self.flip_cols,
+            ..self
+        }
+    }
+
+    /// rotates a quarter turn clockwise, so the first column becomes the first row
+    pub fn rotated_clockwise(self) -> View<'a, T> {
+        self.flipped_vertically().transposed()
+    }
+
+    /// rotates a quarter turn counter-clockwise, so the first row becomes the first column
+    pub fn rotated_counterclockwise(self) -> View<'a, T> {
+        self.flipped_horizontally().transposed()
+    }
+
+    pub fn contains(&self, (row, col): Position) -> bool {
+        row < self.height() && col < self.width()
+    }
+
+    /// translates a position in this view to the corresponding position in the grid
+    pub fn grid_position(&self, (row, col): Position) -> Position {
+        assert!(
+            self.contains((row, col)),
+            "position {:?} is out of range",
+            (row, col)
+        );
+
+        let row = if self.flip_rows {
+            self.height() - 1 - row
+        } else {
+            row
+        };
+        let col = if self.flip_cols {
+            self.width() - 1 - col
+        } else {
+            col
+        };
+
+        if self.transpose {
+            (col, row)
+        } else {
+            (row, col)
+        }
+    }
+
+    pub fn get(&self, position: Position) -> Option<&'a T> {
+        if self.contains(position) {
+            Some(&self.grid[self.grid_position(position)])
+        } else {
+            None
+        }
+    }
+
+    /// all positions in this view, in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width();
+        (0..self.height()).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// the cells of a row, from left to right
+    pub fn row(&self, row: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..self.width()).map(move |col| &view.grid[view.grid_position((row, col))])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T>> {
+        let view = *self;
+        (0..self.height()).map(move |row| view.row(row))
+    }
+
+    /// copies the view into a new grid in its own orientation
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_vec(self.rows().flatten().cloned().collect(), self.width())
+    }
+}
+
+impl<T> Index<Position> for View<'_, T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &Self::Output {
+        &self.grid[self.grid_position(position)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGITS: &str = "\
+123
+456";
+
+    fn digits() -> Grid<u32> {
+        Grid::parse_char_map(0, DIGITS, |_, c| {
+            c.to_digit(10)
+                .ok_or_else(|| AocError::parse(0, format!("not a digit: '{}'", c)))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_char_map() {
+        let grid = digits();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+
+        let error = Grid::parse_char_map(0, "12\n3x", |_, c| {
+            c.to_digit(10)
+                .ok_or_else(|| AocError::parse(0, format!("not a digit: '{}'", c)))
+        })
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 0, line 2, column 2: not a digit: 'x'"
+        );
+
+        let error = Grid::parse_char_map(0, "12\n345", |_, c| Ok(c)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 0, line 2: expected 2 columns, found 3"
+        );
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = digits();
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+
+        let columns: Vec<Vec<_>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, [[1, 4], [2, 5], [3, 6]]);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = digits();
+
+        let neighbours: Vec<_> = grid.neighbours4((0, 0)).collect();
+        assert_eq!(neighbours, [(0, 1), (1, 0)]);
+
+        let neighbours: Vec<_> = grid.neighbours4((1, 1)).collect();
+        assert_eq!(neighbours, [(0, 1), (1, 0), (1, 2)]);
+
+        let neighbours: Vec<_> = grid.neighbours8((0, 1)).collect();
+        assert_eq!(neighbours, [(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = digits();
+        let view = grid.view();
+
+        assert_eq!(view.to_grid(), grid);
+        assert_eq!(
+            view.transposed().to_grid(),
+            Grid::from_vec(vec![1, 4, 2, 5, 3, 6], 2)
+        );
+        assert_eq!(
+            view.rotated_clockwise().to_grid(),
+            Grid::from_vec(vec![4, 1, 5, 2, 6, 3], 2)
+        );
+        assert_eq!(
+            view.rotated_counterclockwise().to_grid(),
+            Grid::from_vec(vec![3, 6, 2, 5, 1, 4], 2)
+        );
+        assert_eq!(
+            view.flipped_horizontally().to_grid(),
+            Grid::from_vec(vec![3, 2, 1, 6, 5, 4], 3)
+        );
+
+        let rotated = view
+            .rotated_clockwise()
+            .rotated_clockwise()
+            .rotated_clockwise()
+            .rotated_clockwise();
+        assert_eq!(rotated.to_grid(), grid);
+
+        let rotated = view.rotated_clockwise();
+        assert_eq!(rotated[(0, 0)], 4);
+        assert_eq!(rotated.grid_position((0, 0)), (1, 0));
+    }
+}
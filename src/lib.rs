@@ -2,6 +2,14 @@ extern crate core;
 
 use aoc_runner_derive::aoc_lib;
 
+pub mod cli;
+pub mod error;
+pub mod grid;
+pub mod ocr;
+pub mod ppm;
+pub mod search;
+pub mod solutions;
+
 pub mod day1;
 pub mod day2;
 pub mod day3;
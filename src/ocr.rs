@@ -0,0 +1,167 @@
+//! Reads the capital letters Advent of Code draws on pixel displays, in its 4x6 font.
+
+use crate::grid::Grid;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+/// glyphs are separated by a blank column
+const GLYPH_SPACING: usize = GLYPH_WIDTH + 1;
+
+/// every known glyph, row by row. the font's Y is five pixels wide, unlike every other letter, so
+/// it isn't here and displays containing one can't be read.
+const FONT: [(char, &str); 17] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OcrError {
+    /// the display isn't one glyph high
+    WrongHeight(usize),
+    /// the (0-based) positions of glyphs that aren't in the font
+    Unrecognised(Vec<usize>),
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::WrongHeight(height) => write!(
+                f,
+                "display is {} pixels high, but glyphs are {}",
+                height, GLYPH_HEIGHT
+            ),
+            OcrError::Unrecognised(positions) => {
+                let positions = positions
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "unrecognised glyphs at positions {}", positions)
+            }
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+/// reads the letters on a display of lit (`true`) and unlit pixels
+pub fn read_display(display: &Grid<bool>) -> Result<String, OcrError> {
+    if display.height() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight(display.height()));
+    }
+
+    let num_glyphs = display.width().div_ceil(GLYPH_SPACING);
+    let mut letters = String::with_capacity(num_glyphs);
+    let mut unrecognised = Vec::new();
+    for glyph in 0..num_glyphs {
+        let left = glyph * GLYPH_SPACING;
+        let pixel = |row, col| display.get((row, left + col)).copied().unwrap_or(false);
+        let letter = FONT.iter().find(|(_, pattern)| {
+            pattern
+                .bytes()
+                .enumerate()
+                .all(|(i, b)| pixel(i / GLYPH_WIDTH, i % GLYPH_WIDTH) == (b == b'#'))
+        });
+        match letter {
+            Some((letter, _)) => letters.push(*letter),
+            None => unrecognised.push(glyph),
+        }
+    }
+
+    if unrecognised.is_empty() {
+        Ok(letters)
+    } else {
+        Err(OcrError::Unrecognised(unrecognised))
+    }
+}
+
+/// reads the letters drawn with `#` for lit pixels and `.` for unlit ones, ignoring blank lines
+pub fn read_text(display: &str) -> Result<String, OcrError> {
+    let rows: Vec<_> = display.lines().filter(|line| !line.is_empty()).collect();
+    let Some(width) = rows.iter().map(|row| row.len()).max() else {
+        return Err(OcrError::WrongHeight(0));
+    };
+    let mut pixels = Grid::new(width, rows.len(), false);
+    for (row, line) in rows.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            pixels[(row, col)] = c == '#';
+        }
+    }
+    read_display(&pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_text() {
+        const DISPLAY: &str = "
+#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.
+";
+        assert_eq!(read_text(DISPLAY), Ok("HELLO".to_owned()));
+    }
+
+    #[test]
+    fn test_read_rendered_display() {
+        // drawn by a real day 10 program
+        const DISPLAY: &str = "
+####.###..#..#.###..#..#.####..##..#..#.
+#....#..#.#..#.#..#.#..#....#.#..#.#..#.
+###..###..#..#.#..#.####...#..#....####.
+#....#..#.#..#.###..#..#..#...#....#..#.
+#....#..#.#..#.#.#..#..#.#....#..#.#..#.
+#....###...##..#..#.#..#.####..##..#..#.
+";
+        assert_eq!(read_text(DISPLAY), Ok("FBURHZCH".to_owned()));
+    }
+
+    #[test]
+    fn test_font_is_consistent() {
+        for (letter, pattern) in FONT {
+            assert_eq!(pattern.len(), GLYPH_WIDTH * GLYPH_HEIGHT, "{}", letter);
+            let display = Grid::from_vec(pattern.bytes().map(|b| b == b'#').collect(), 4);
+            assert_eq!(read_display(&display), Ok(letter.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        const DISPLAY: &str = "
+#..#.####.#..#
+#..#.#....#..#
+####.###..####
+#..#.#....#..#
+#..#.#....#..#
+#..#.#.##.#..#
+";
+        let error = read_text(DISPLAY).unwrap_err();
+        assert_eq!(error, OcrError::Unrecognised(vec![1]));
+        assert_eq!(error.to_string(), "unrecognised glyphs at positions 1");
+
+        assert_eq!(read_text("#..#"), Err(OcrError::WrongHeight(1)));
+        assert_eq!(read_text("\n\n"), Err(OcrError::WrongHeight(0)));
+    }
+}
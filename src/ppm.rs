@@ -0,0 +1,47 @@
+use crate::grid::Grid;
+use std::io::{self, Write};
+
+/// A colour as red, green and blue components.
+pub type Rgb = [u8; 3];
+
+/// writes `image` as a binary (P6) PPM, one pixel per cell
+pub fn write_ppm<W: Write>(image: &Grid<Rgb>, mut writer: W) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", image.width(), image.height())?;
+    for pixel in image.iter() {
+        writer.write_all(pixel)?;
+    }
+    writer.flush()
+}
+
+/// the colour `t` of the way from `from` to `to`, with `t` clamped to `0.0..=1.0`
+pub fn lerp(from: Rgb, to: Rgb, t: f64) -> Rgb {
+    let t = t.clamp(0.0, 1.0);
+    let mut colour = [0; 3];
+    for i in 0..3 {
+        let (from, to) = (from[i] as f64, to[i] as f64);
+        colour[i] = (from + (to - from) * t).round() as u8;
+    }
+    colour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_ppm() {
+        let mut image = Grid::new(2, 1, [0, 0, 0]);
+        image[(0, 1)] = [255, 128, 1];
+
+        let mut out = Vec::new();
+        write_ppm(&image, &mut out).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\x00\x00\x00\xff\x80\x01");
+    }
+
+    #[test]
+    fn test_lerp() {
+        assert_eq!(lerp([0, 100, 255], [255, 100, 0], 0.0), [0, 100, 255]);
+        assert_eq!(lerp([0, 100, 255], [255, 100, 0], 0.5), [128, 100, 128]);
+        assert_eq!(lerp([0, 100, 255], [255, 100, 0], 2.0), [255, 100, 0]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// A graph of nodes joined by costed, one-way steps.
+pub trait Graph {
+    type Node: Copy + Eq + Hash;
+
+    /// the nodes one step from `node`, each with the cost of the step
+    fn neighbours(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, u64)>;
+}
+
+/// A route found by a search, from its start to the goal it reached.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Path<N> {
+    /// every node on the route, including both ends
+    pub nodes: Vec<N>,
+    pub cost: u64,
+}
+
+impl<N: Copy> Path<N> {
+    pub fn steps(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    pub fn start(&self) -> N {
+        self.nodes[0]
+    }
+
+    pub fn goal(&self) -> N {
+        self.nodes[self.nodes.len() - 1]
+    }
+}
+
+/// follows parent pointers back from `goal` to the node without a parent
+fn route<N: Copy + Eq + Hash>(parents: &HashMap<N, N>, goal: N) -> Vec<N> {
+    let mut nodes = vec![goal];
+    let mut current = goal;
+    while let Some(&parent) = parents.get(&current) {
+        nodes.push(parent);
+        current = parent;
+    }
+    nodes.reverse();
+    nodes
+}
+
+/// the route from `start` with the fewest steps to the nearest node satisfying `is_goal`. step
+/// costs are ignored, and the path's cost is its number of steps.
+pub fn bfs<G, F>(graph: &G, start: G::Node, mut is_goal: F) -> Option<Path<G::Node>>
+where
+    G: Graph,
+    F: FnMut(G::Node) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(node) {
+            let nodes = route(&parents, node);
+            let cost = nodes.len() as u64 - 1;
+            return Some(Path { nodes, cost });
+        }
+
+        for (next, _) in graph.neighbours(node) {
+            if visited.insert(next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// the cheapest route from `start` to a node satisfying `is_goal`
+pub fn dijkstra<G, F>(graph: &G, start: G::Node, is_goal: F) -> Option<Path<G::Node>>
+where
+    G: Graph,
+    G::Node: Ord,
+    F: FnMut(G::Node) -> bool,
+{
+    a_star(graph, start, is_goal, |_| 0)
+}
+
+/// the cheapest route from `start` to a node satisfying `is_goal`, exploring nodes in order of
+/// their cost so far plus `heuristic`. the heuristic must never overestimate the remaining cost,
+/// or decrease by more than a step's cost along it, for the route to be the cheapest.
+pub fn a_star<G, F, H>(
+    graph: &G,
+    start: G::Node,
+    mut is_goal: F,
+    mut heuristic: H,
+) -> Option<Path<G::Node>>
+where
+    G: Graph,
+    G::Node: Ord,
+    F: FnMut(G::Node) -> bool,
+    H: FnMut(G::Node) -> u64,
+{
+    let mut costs = HashMap::from([(start, 0)]);
+    let mut parents = HashMap::new();
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, start))]);
+
+    while let Some(Reverse((_, cost, node))) = open.pop() {
+        if cost > costs[&node] {
+            continue; // already reached more cheaply
+        }
+        if is_goal(node) {
+            return Some(Path {
+                nodes: route(&parents, node),
+                cost,
+            });
+        }
+
+        for (next, step) in graph.neighbours(node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|&known| next_cost < known) {
+                costs.insert(next, next_cost);
+                parents.insert(next, node);
+                open.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// steps between numbered nodes, from an adjacency list
+    struct Edges(Vec<Vec<(usize, u64)>>);
+
+    impl Graph for Edges {
+        type Node = usize;
+
+        fn neighbours(&self, node: usize) -> impl Iterator<Item = (usize, u64)> {
+            self.0[node].iter().copied()
+        }
+    }
+
+    // 0 -> 3 directly costs 10, or 3 via 1 and 2 for 1 each, and 4 is unreachable
+    fn graph() -> Edges {
+        Edges(vec![
+            vec![(1, 1), (3, 10)],
+            vec![(2, 1)],
+            vec![(3, 1)],
+            vec![],
+            vec![(0, 1)],
+        ])
+    }
+
+    #[test]
+    fn test_fewest_steps_and_cheapest() {
+        let graph = graph();
+        let fewest = bfs(&graph, 0, |n| n == 3).unwrap();
+        assert_eq!(fewest.nodes, [0, 3]);
+        assert_eq!(fewest.cost, 1);
+
+        let cheapest = dijkstra(&graph, 0, |n| n == 3).unwrap();
+        assert_eq!(cheapest.nodes, [0, 1, 2, 3]);
+        assert_eq!((cheapest.cost, cheapest.steps()), (3, 3));
+
+        let remaining = [3, 2, 1, 0, 0];
+        let guided = a_star(&graph, 0, |n| n == 3, |n| remaining[n]).unwrap();
+        assert_eq!(guided, cheapest);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let graph = graph();
+        assert_eq!(bfs(&graph, 0, |n| n == 4), None);
+        assert_eq!(dijkstra(&graph, 3, |n| n == 0), None);
+        assert_eq!(dijkstra(&graph, 4, |n| n == 4).unwrap().nodes, [4]);
+    }
+}
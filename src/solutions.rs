@@ -0,0 +1,121 @@
+use crate::error::AocError;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day2, day3, day4, day5, day6, day7, day8, day9,
+};
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// The answer to one part of a day, with how long it took to produce.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Solved {
+    pub answer: String,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+/// A day's puzzle: how to parse its input, and how to solve each part.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, AocError>;
+    fn part1(input: &Self::Input) -> Result<Self::Answer1, AocError>;
+    fn part2(input: &Self::Input) -> Result<Self::Answer2, AocError>;
+}
+
+/// A type-erased [`Solution`], so days can be enumerated and driven without knowing their types.
+pub trait AnySolution: Sync {
+    fn day(&self) -> u8;
+
+    /// parses `input` and solves `part` (1-based), or returns `None` if there is no such part
+    fn solve(&self, part: u8, input: &str) -> Option<Result<Solved, AocError>>;
+}
+
+impl<S: Solution + Sync> AnySolution for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn solve(&self, part: u8, input: &str) -> Option<Result<Solved, AocError>> {
+        if !(1..=2).contains(&part) {
+            return None;
+        }
+
+        let start = Instant::now();
+        let parsed = match S::parse(input) {
+            Ok(parsed) => parsed,
+            Err(e) => return Some(Err(e)),
+        };
+        let generator = start.elapsed();
+
+        let start = Instant::now();
+        let answer = match part {
+            1 => S::part1(&parsed).map(|answer| answer.to_string()),
+            _ => S::part2(&parsed).map(|answer| answer.to_string()),
+        };
+        let runner = start.elapsed();
+
+        Some(answer.map(|answer| Solved {
+            answer,
+            generator,
+            runner,
+        }))
+    }
+}
+
+/// every day's solution, in order
+pub const SOLUTIONS: [&dyn AnySolution; 15] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+];
+
+/// the last day with a solution
+pub const LAST_DAY: u8 = SOLUTIONS.len() as u8;
+
+/// runs the generator and solver for a day and part (both 1-based) against `input`, or returns
+/// `None` if there is no such solution
+pub fn solve(day: u8, part: u8, input: &str) -> Option<Result<Solved, AocError>> {
+    let solution = SOLUTIONS.get(day.checked_sub(1)? as usize)?;
+    solution.solve(part, input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let solved = solve(2, 1, "A Y\nB X\nC Z").unwrap().unwrap();
+        assert_eq!(solved.answer, "15");
+
+        let error = solve(2, 1, "A Y\nB X\nC").unwrap().unwrap_err();
+        assert_eq!(error.line(), Some(3));
+
+        assert_eq!(solve(2, 3, ""), None);
+        assert_eq!(solve(0, 1, ""), None);
+        assert_eq!(solve(LAST_DAY + 1, 1, ""), None);
+    }
+
+    #[test]
+    fn test_solutions_are_in_order() {
+        for (i, solution) in SOLUTIONS.iter().enumerate() {
+            assert_eq!(solution.day() as usize, i + 1);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Checks every solver against the known-correct answers for the checked-in puzzle inputs.
+
+use aoc_2022::solutions::{solve, LAST_DAY};
+use std::fs;
+use toml::Value;
+
+const INPUT_DIR: &str = "input/2022";
+
+fn answers() -> toml::value::Table {
+    let answers = fs::read_to_string(format!("{}/answers.toml", INPUT_DIR))
+        .expect("could not read answers.toml");
+    match answers.parse().expect("could not parse answers.toml") {
+        Value::Table(table) => table,
+        _ => unreachable!("a toml document is always a table"),
+    }
+}
+
+fn check_day(day: u8) {
+    let answers = answers();
+    let expected = answers
+        .get(&format!("day{}", day))
+        .and_then(Value::as_table)
+        .unwrap_or_else(|| panic!("no answers for day {}", day));
+
+    let input = fs::read_to_string(format!("{}/day{}.txt", INPUT_DIR, day))
+        .unwrap_or_else(|e| panic!("could not read input for day {}: {}", day, e));
+
+    for part in 1..=2 {
+        let expected = match expected.get(&format!("part{}", part)) {
+            Some(Value::String(s)) => s.to_owned(),
+            Some(Value::Integer(i)) => i.to_string(),
+            Some(v) => panic!(
+                "unexpected answer type for day {} part {}: {}",
+                day, part, v
+            ),
+            None => panic!("no answer for day {} part {}", day, part),
+        };
+
+        let solved = solve(day, part, &input)
+            .unwrap_or_else(|| panic!("no solution for day {} part {}", day, part))
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        assert_eq!(solved.answer, expected, "day {} part {}", day, part);
+    }
+}
+
+#[test]
+fn test_every_day_has_answers() {
+    let answers = answers();
+    for day in 1..=LAST_DAY {
+        assert!(
+            answers.contains_key(&format!("day{}", day)),
+            "no answers for day {}",
+            day
+        );
+    }
+    assert_eq!(answers.len(), LAST_DAY as usize, "answers for unknown days");
+}
+
+macro_rules! answer_tests {
+    ($($name:ident: $day:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check_day($day);
+            }
+        )*
+    };
+}
+
+answer_tests! {
+    test_day1: 1,
+    test_day2: 2,
+    test_day3: 3,
+    test_day4: 4,
+    test_day5: 5,
+    test_day6: 6,
+    test_day7: 7,
+    test_day8: 8,
+    test_day9: 9,
+    test_day10: 10,
+    test_day11: 11,
+    test_day12: 12,
+    test_day13: 13,
+    test_day14: 14,
+    test_day15: 15,
+}